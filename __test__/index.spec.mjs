@@ -1,7 +1,153 @@
+import { mkdirSync, mkdtempSync, readFileSync, writeFileSync } from 'node:fs'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+
 import test from 'ava'
 
-import { sum } from '../index.js'
+import { generateTsconfig, mergeTsconfig, run } from '../index.js'
+
+test('generateTsconfig uses the --yes defaults', (t) => {
+  const { compilerOptions } = generateTsconfig()
+  t.is(compilerOptions.module, 'NodeNext')
+  t.is(compilerOptions.outDir, 'dist')
+  t.true(compilerOptions.strict)
+  t.deepEqual(compilerOptions.lib, [compilerOptions.target])
+})
+
+test('generateTsconfig only detects defaults when asked', (t) => {
+  const { compilerOptions } = generateTsconfig({ detect: false })
+  t.is(compilerOptions.target, 'es2022')
+  t.is(compilerOptions.module, 'NodeNext')
+
+  // The CLI detects in the project directory rather than the current one
+  const dir = mkdtempSync(join(tmpdir(), 'detect-'))
+  writeFileSync(join(dir, 'package.json'), JSON.stringify({ engines: { node: '>=16' } }))
+  run(['--yes', '--name', dir])
+  const tsconfig = JSON.parse(readFileSync(join(dir, 'tsconfig.json'), 'utf8'))
+  t.is(tsconfig.compilerOptions.target, 'es2021')
+  t.is(tsconfig.compilerOptions.module, 'Node16')
+})
+
+test('generateTsconfig applies the given options', (t) => {
+  const { compilerOptions } = generateTsconfig({
+    strictness: 'strict',
+    target: 'es2019',
+    isTranspiler: false,
+    isLibrary: true,
+    isDom: true,
+  })
+  t.is(compilerOptions.module, 'preserve')
+  t.true(compilerOptions.emitDeclarationOnly)
+  t.is(compilerOptions.noEmit, undefined)
+  t.true(compilerOptions.declaration)
+  t.true(compilerOptions.noUncheckedIndexedAccess)
+  t.is(compilerOptions.target, 'es2019')
+  t.deepEqual(compilerOptions.lib, ['es2019', 'dom', 'dom.iterable'])
+})
+
+test('mergeTsconfig keeps unrelated keys and reports conflicts', (t) => {
+  const existing = {
+    include: ['src'],
+    compilerOptions: { target: 'es5', module: 'commonjs', paths: { '~/*': ['./src/*'] } },
+  }
+  const { tsconfig, conflicts } = mergeTsconfig(existing, {}, { resolutions: { module: 'generated' } })
+  t.deepEqual(tsconfig.include, ['src'])
+  t.deepEqual(tsconfig.compilerOptions.paths, { '~/*': ['./src/*'] })
+  t.is(tsconfig.compilerOptions.target, 'es5')
+  t.is(tsconfig.compilerOptions.module, 'NodeNext')
+  t.deepEqual(
+    conflicts.map(({ key, resolution }) => [key, resolution]),
+    [
+      ['module', 'generated'],
+      ['target', 'existing'],
+    ],
+  )
+})
+
+test('generateTsconfig starts from a preset', (t) => {
+  const { compilerOptions } = generateTsconfig({ preset: 'node-lib' })
+  t.true(compilerOptions.declaration)
+  t.true(compilerOptions.declarationMap)
+  t.is(compilerOptions.rootDir, 'src')
+  const svelte = generateTsconfig({ preset: 'node-app', framework: 'svelte' }).compilerOptions
+  t.deepEqual(svelte.types, ['node', 'svelte'])
+  t.throws(() => generateTsconfig({ preset: 'nope' }))
+})
+
+test('generateTsconfig sets up the runtime', (t) => {
+  const node = generateTsconfig({ runtime: 'node' }).compilerOptions
+  t.deepEqual(node.types, ['node'])
+  const worker = generateTsconfig({ runtime: 'webworker', target: 'es2022' }).compilerOptions
+  t.deepEqual(worker.lib, ['es2022', 'webworker'])
+  t.is(worker.module, 'ESNext')
+  t.is(worker.moduleResolution, 'bundler')
+  t.is(worker.types, undefined)
+  const bun = generateTsconfig({ runtime: 'bun' }).compilerOptions
+  t.is(bun.moduleResolution, 'bundler')
+  t.deepEqual(bun.types, ['bun'])
+  const deno = generateTsconfig({ runtime: 'deno', strictness: 'strict' }).compilerOptions
+  t.deepEqual(deno.lib, ['deno.window'])
+  t.true(deno.noUncheckedIndexedAccess)
+  t.is(deno.module, undefined)
+})
+
+test('generateTsconfig sets up JSX for the framework', (t) => {
+  const preact = generateTsconfig({ framework: 'preact' }).compilerOptions
+  t.is(preact.jsx, 'react-jsx')
+  t.is(preact.jsxImportSource, 'preact')
+  const svelte = generateTsconfig({ framework: 'svelte', runtime: 'bun' }).compilerOptions
+  t.is(svelte.jsx, undefined)
+  t.deepEqual(svelte.types, ['bun', 'svelte'])
+})
+
+test('generateTsconfig picks the module format', (t) => {
+  const cjs = generateTsconfig({ moduleFormat: 'cjs' }).compilerOptions
+  t.is(cjs.module, 'CommonJS')
+  t.false(cjs.verbatimModuleSyntax)
+  const dual = generateTsconfig({ moduleFormat: 'dual' }).compilerOptions
+  t.true(dual.noEmit)
+  t.is(dual.outDir, undefined)
+})
+
+test('generateTsconfig resolves modules like the bundler', (t) => {
+  const vite = generateTsconfig({ isTranspiler: false, runtime: 'browser', bundler: 'vite' }).compilerOptions
+  t.is(vite.moduleResolution, 'bundler')
+  t.true(vite.allowImportingTsExtensions)
+  t.deepEqual(vite.types, ['vite/client'])
+  const webpack = generateTsconfig({ isTranspiler: false, bundler: 'webpack' }).compilerOptions
+  t.is(webpack.moduleResolution, 'bundler')
+  t.is(webpack.allowImportingTsExtensions, undefined)
+})
+
+test('generateTsconfig emits only declarations for bundled libraries', (t) => {
+  const declarations = generateTsconfig({
+    isTranspiler: false,
+    isLibrary: true,
+    isolatedDeclarations: true,
+    declarationDir: 'types',
+  }).compilerOptions
+  t.true(declarations.emitDeclarationOnly)
+  t.true(declarations.isolatedDeclarations)
+  t.is(declarations.declarationDir, 'types')
+  const nothing = generateTsconfig({ isTranspiler: false, isLibrary: true, declarationOnly: false }).compilerOptions
+  t.true(nothing.noEmit)
+  t.is(nothing.declaration, undefined)
+})
 
-test('sum from native', (t) => {
-  t.is(sum(1, 2), 3)
+test('check-lib reports entry points tsc does not emit', (t) => {
+  const dir = mkdtempSync(join(tmpdir(), 'check-lib-'))
+  mkdirSync(join(dir, 'src'))
+  writeFileSync(join(dir, 'src', 'index.ts'), '')
+  writeFileSync(
+    join(dir, 'tsconfig.json'),
+    JSON.stringify({ compilerOptions: { outDir: 'dist', rootDir: 'src', declaration: true } }),
+  )
+  writeFileSync(
+    join(dir, 'package.json'),
+    JSON.stringify({ exports: { '.': { default: './lib/index.js', types: './dist/index.d.ts' } }, files: ['dist'] }),
+  )
+  const error = t.throws(() => run(['check-lib', join(dir, 'tsconfig.json')]))
+  t.is(error.code, 'ERR_LIBRARY')
+  t.regex(error.message, /lists types after default/)
+  t.regex(error.message, /points to \.\/lib\/index\.js/)
 })
@@ -1,6 +1,12 @@
 #!/usr/bin/env node
 const cli = require(".")
-cli.run().catch((e) => {
-    console.error(e)
+try {
+    cli.run(process.argv.slice(2))
+} catch (e) {
+    if (e.code === "ERR_INVALID_ARGS") {
+        console.error(e.message)
+        process.exit(2)
+    }
+    console.error(`Error: ${e.message}`)
     process.exit(1)
-})
\ No newline at end of file
+}
\ No newline at end of file
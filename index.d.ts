@@ -3,4 +3,165 @@
 
 /* auto-generated by NAPI-RS */
 
-export declare function run(): void
+export const enum Bundler {
+  Vite = 'vite',
+  Esbuild = 'esbuild',
+  Webpack = 'webpack',
+  Rollup = 'rollup',
+  Tsup = 'tsup',
+  Bun = 'bun'
+}
+export const enum Framework {
+  React = 'react',
+  Preact = 'preact',
+  Solid = 'solid',
+  Vue = 'vue',
+  Svelte = 'svelte',
+  None = 'none'
+}
+/**
+ * Which side wins when a compiler option differs between the existing
+ * tsconfig and the generated one.
+ */
+export const enum ConflictResolution {
+  Existing = 'existing',
+  Generated = 'generated'
+}
+export const enum ModuleFormat {
+  Esm = 'esm',
+  Cjs = 'cjs',
+  /**
+   * ES modules and CommonJS, built by `tsconfig.esm.json` and
+   * `tsconfig.cjs.json` on top of a shared `tsconfig.json`.
+   */
+  Dual = 'dual'
+}
+export const enum Runtime {
+  Node = 'node',
+  Bun = 'bun',
+  Deno = 'deno',
+  /** A browser page, with the DOM. */
+  Browser = 'browser',
+  /** A web or service worker, without the DOM. */
+  WebWorker = 'webworker',
+  /** Edge runtimes such as Cloudflare Workers or Vercel Edge Functions. */
+  Edge = 'edge'
+}
+export const enum Target {
+  Es2017 = 'es2017',
+  Es2018 = 'es2018',
+  Es2019 = 'es2019',
+  Es2020 = 'es2020',
+  Es2021 = 'es2021',
+  Es2022 = 'es2022',
+  Es2023 = 'es2023',
+  Es2024 = 'es2024',
+  EsNext = 'esnext'
+}
+export const enum Strictness {
+  Off = 'off',
+  On = 'on',
+  Strict = 'strict'
+}
+/**
+ * Options accepted by `generateTsconfig`, mirroring the interactive
+ * questions. Anything left out takes the same default as `--yes`.
+ */
+export interface TsconfigOptions {
+  /**
+   * One of the built-in presets, e.g. `node-lib`. The other options
+   * override its answers.
+   */
+  preset?: string
+  strictness?: Strictness
+  /**
+   * Sets both `target` and `lib`. Defaults to `es2022`, or with `detect`
+   * to the newest version the project's Node.js supports.
+   */
+  target?: Target
+  isTranspiler?: boolean
+  /**
+   * What tsc emits when `isTranspiler` is set. A `dual` build also needs
+   * the `tsconfig.esm.json` and `tsconfig.cjs.json` the CLI writes next to
+   * this config.
+   */
+  moduleFormat?: ModuleFormat
+  isLibrary?: boolean
+  isMonorepo?: boolean
+  runtime?: Runtime
+  /**
+   * Sets up JSX for the framework. With `detect` set, defaults to the one
+   * the `package.json` in the current directory depends on.
+   */
+  framework?: Framework
+  /**
+   * Sets up module resolution and types for the bundler when
+   * `isTranspiler` is `false`.
+   */
+  bundler?: Bundler
+  /**
+   * For a library with `isTranspiler` set to `false`, whether tsc still
+   * emits its declarations. Defaults to `true`.
+   */
+  declarationOnly?: boolean
+  /** Turns on `isolatedDeclarations` along with `declarationOnly`. */
+  isolatedDeclarations?: boolean
+  /** Where `declarationOnly` writes the declarations. Defaults to `dist`. */
+  declarationDir?: string
+  /** Deprecated, use `runtime`: `true` means `browser` and `false` `node`. */
+  isDom?: boolean
+  /**
+   * Whether options left out default to what the CLI detects: the Node.js
+   * version, runtime, framework, bundler and browserslist of the project in
+   * the current directory. Defaults to `false`, so the result only depends
+   * on the options given.
+   */
+  detect?: boolean
+}
+/**
+ * Runs the initializer with `args` as the command line arguments, excluding
+ * the program name (e.g. `process.argv.slice(2)` from `bin.js`).
+ *
+ * Failures are thrown as an `Error` whose `code` is one of
+ * `ERR_INVALID_ARGS`, `ERR_PROMPT`, `ERR_IO`, `ERR_JSON`, `ERR_EXISTS`,
+ * `ERR_REFERENCES` or `ERR_LIBRARY`.
+ */
+export declare function run(args?: Array<string> | undefined | null): void
+/**
+ * Same as `run`, but prompts and writes files on the libuv thread pool so
+ * the event loop is not blocked. The returned promise rejects with the same
+ * coded errors `run` throws.
+ */
+export declare function runAsync(args?: Array<string> | undefined | null): Promise<void>
+/**
+ * Returns the tsconfig the CLI would write for `options`, without prompting
+ * or writing any files. Options left out take the `--yes` defaults, or with
+ * `detect` the ones the CLI would detect in the current directory.
+ */
+export declare function generateTsconfig(options?: TsconfigOptions | undefined | null): { compilerOptions: Record<string, unknown> }
+/**
+ * How `mergeTsconfig` settles compiler options that the existing config
+ * already sets to a different value.
+ */
+export interface MergeOptions {
+  /** Per compiler option overrides, e.g. `{ target: 'existing' }`. */
+  resolutions?: Record<string, ConflictResolution>
+  /** Used for conflicts not listed in `resolutions`. Defaults to `existing`. */
+  onConflict?: ConflictResolution
+}
+export interface TsconfigConflict {
+  key: string
+  existing: unknown
+  generated: unknown
+  resolution: ConflictResolution
+}
+export interface MergeResult {
+  tsconfig: Record<string, unknown>
+  conflicts: Array<TsconfigConflict>
+}
+/**
+ * Merges the tsconfig generated for `options` into `existing`, keeping keys
+ * like `include`, `paths` and `references`, and reports every compiler
+ * option the two disagree on along with how it was resolved.
+ */
+export declare function mergeTsconfig(existing: Record<string, unknown>, options?: TsconfigOptions | undefined | null, merge?: MergeOptions | undefined | null): MergeResult
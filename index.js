@@ -310,8 +310,16 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { run } = nativeBinding
+const { Bundler, Framework, ConflictResolution, ModuleFormat, Runtime, Target, Strictness, run, runAsync, generateTsconfig, mergeTsconfig } = nativeBinding
 
+module.exports.Bundler = Bundler
+module.exports.Framework = Framework
+module.exports.ConflictResolution = ConflictResolution
+module.exports.ModuleFormat = ModuleFormat
+module.exports.Runtime = Runtime
+module.exports.Target = Target
+module.exports.Strictness = Strictness
 module.exports.run = run
-
-run()
+module.exports.runAsync = runAsync
+module.exports.generateTsconfig = generateTsconfig
+module.exports.mergeTsconfig = mergeTsconfig
@@ -0,0 +1,343 @@
+//! Generates a `tsconfig.json` for every package of an existing workspace,
+//! inferring from each `package.json` whether it is a library and which
+//! runtime it is for.
+
+use crate::bundler::{self, Bundler};
+use crate::error::{Error, ErrorCode, Result};
+use crate::framework::{self, Framework};
+use crate::graph;
+use crate::node;
+use crate::runtime::{self, Runtime};
+use crate::workspace::{Package, Workspace};
+use crate::write::{self, Written};
+use crate::{
+  config_files, declaration_only, detect_target, generate_tsconfig, monorepo, project_dir,
+  prompt_options, CliArgs, ProjectOptions,
+};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::thread;
+
+pub fn init_workspace(mut args: CliArgs) -> Result<()> {
+  args.project_name.get_or_insert_with(|| ".".into());
+  args.is_monorepo.get_or_insert(true);
+
+  // Ask the questions shared by every package once, up front; the per
+  // package answers are filled in from each manifest below
+  let shared = prompt_options(&CliArgs {
+    is_library: Some(args.is_library.unwrap_or(false)),
+    runtime: Some(args.runtime.unwrap_or(Runtime::Node)),
+    framework: Some(args.framework.unwrap_or(Framework::None)),
+    // Taken per package below
+    bundler: Some(args.bundler.unwrap_or(Bundler::Vite)),
+    ..args.clone()
+  })?;
+  let root = project_dir(&shared.project_name)?;
+  let workspace = Workspace::discover(&root)?
+    .filter(|workspace| !workspace.packages.is_empty())
+    .ok_or_else(|| {
+      Error::new(
+        ErrorCode::InvalidArgs,
+        format!("no workspace packages found in {}", root.display()),
+      )
+    })?;
+
+  // Packages are written from several threads, so nothing may prompt:
+  // existing files are skipped unless --force or --merge say what to do with
+  // them, conflicts keep the existing value
+  let args = CliArgs { yes: true, ..args };
+  let options: Vec<ProjectOptions> = workspace
+    .packages
+    .iter()
+    .map(|package| package_options(&workspace, package, &shared, &args))
+    .collect::<Result<_>>()?;
+  for (package, options) in workspace.packages.iter().zip(&options) {
+    for warning in bundler::warnings(options) {
+      eprintln!("{}: warning: {}", package.dir, warning);
+    }
+  }
+  let results: Vec<Result<Written>> = if args.dry_run || args.stdout {
+    // Their output is several lines per package and would interleave
+    workspace
+      .packages
+      .iter()
+      .zip(&options)
+      .map(|(package, options)| init_package(&workspace, package, options, &args))
+      .collect()
+  } else {
+    thread::scope(|scope| {
+      let handles: Vec<_> = workspace
+        .packages
+        .iter()
+        .zip(&options)
+        .map(|(package, options)| scope.spawn(|| init_package(&workspace, package, options, &args)))
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().expect("package worker panicked"))
+        .collect()
+    })
+  };
+
+  let summary = summary(&workspace, &options, &args, &results);
+  // Keep stdout to the JSON itself with --stdout
+  if args.stdout {
+    eprint!("{}", summary);
+  } else {
+    print!("{}", summary);
+  }
+
+  let failed: Vec<&Error> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+  if let Some(first) = failed.first() {
+    return Err(Error::new(
+      first.code(),
+      format!(
+        "{} of {} packages failed",
+        failed.len(),
+        workspace.packages.len()
+      ),
+    ));
+  }
+  if args.dry_run || args.stdout {
+    return Ok(());
+  }
+  let configs: Vec<PathBuf> = workspace
+    .packages
+    .iter()
+    .zip(&options)
+    .filter(|(_, options)| options.runtime != Runtime::Deno)
+    .map(|(package, _)| workspace.root.join(&package.dir).join("tsconfig.json"))
+    .collect();
+  graph::warn(&configs);
+  Ok(())
+}
+
+fn init_package(
+  workspace: &Workspace,
+  package: &Package,
+  options: &ProjectOptions,
+  args: &CliArgs,
+) -> Result<Written> {
+  let mut tsconfig = generate_tsconfig(options);
+  let references = workspace.references(package);
+  if !references.is_empty() && options.runtime != Runtime::Deno {
+    tsconfig["references"] = monorepo::reference_list(&references);
+  }
+
+  // What happened to the main config is what the summary reports
+  let dir = workspace.root.join(&package.dir);
+  let mut written = Written::Skipped;
+  for (i, (path, config)) in config_files(options, &dir, tsconfig).iter().enumerate() {
+    if args.stdout {
+      println!("// {}", path.display());
+    } else if !args.force && !args.merge && path.exists() {
+      continue;
+    }
+    let (file_written, _) = write::write_config(args, path, config)?;
+    if i == 0 {
+      written = file_written;
+    }
+  }
+  Ok(written)
+}
+
+/// The shared answers, with `is_library`, the runtime, the framework and the
+/// target taken from the package's own files unless they were given on the
+/// command line (or, for the target, picked at the shared prompt).
+fn package_options(
+  workspace: &Workspace,
+  package: &Package,
+  shared: &ProjectOptions,
+  args: &CliArgs,
+) -> Result<ProjectOptions> {
+  let dir = workspace.root.join(&package.dir);
+  let mut options = ProjectOptions {
+    project_name: package.dir.clone(),
+    is_library: args.is_library.unwrap_or_else(|| package.is_library()),
+    runtime: args
+      .runtime
+      .or_else(|| runtime::detect(&dir))
+      .unwrap_or(Runtime::Node),
+    framework: args
+      .framework
+      .or_else(|| framework::detect(&dir))
+      .unwrap_or(Framework::None),
+    ..shared.clone()
+  };
+  options.bundler = args
+    .bundler
+    .or_else(|| bundler::detect(&dir, options.runtime))
+    .filter(|_| !options.is_transpiler);
+  if options.is_library && !options.is_transpiler && options.runtime != Runtime::Deno {
+    options.declaration_only = declaration_only(args, options.preset)?;
+  }
+  let pinned = node::pinned(&dir);
+  if let Some(pinned) = pinned {
+    options.node_major = Some(pinned.major);
+  }
+  if !shared.target_chosen {
+    if let Some((target, _)) = detect_target(Some(&dir), options.runtime, pinned) {
+      options.target = target;
+    }
+  }
+  Ok(options)
+}
+
+/// A table with one row per package: what kind of project it was taken for
+/// and what happened to its tsconfig.
+fn summary(
+  workspace: &Workspace,
+  options: &[ProjectOptions],
+  args: &CliArgs,
+  results: &[Result<Written>],
+) -> String {
+  let width = workspace
+    .packages
+    .iter()
+    .map(|package| package.dir.len())
+    .chain(["Package".len()])
+    .max()
+    .unwrap_or_default();
+
+  let mut table = String::new();
+  let _ = writeln!(table, "{:<width$}  {:<26}  Result", "Package", "Kind");
+  for ((package, options), result) in workspace.packages.iter().zip(options).zip(results) {
+    let kind = format!(
+      "{}, {}, {}",
+      if options.is_library { "library" } else { "app" },
+      options.runtime.as_str(),
+      options.target.as_str(),
+    );
+    let result = match result {
+      Ok(written) => outcome(*written, args.dry_run).to_string(),
+      Err(e) => format!("failed: {}", e),
+    };
+    let _ = writeln!(table, "{:<width$}  {:<26}  {}", package.dir, kind, result);
+  }
+  table
+}
+
+fn outcome(written: Written, dry_run: bool) -> &'static str {
+  match (written, dry_run) {
+    (Written::Created, false) => "created",
+    (Written::Replaced, false) => "replaced",
+    (Written::Merged, false) => "merged",
+    (Written::Created, true) => "would create",
+    (Written::Replaced, true) => "would replace",
+    (Written::Merged, true) => "would merge",
+    (Written::Skipped, _) => "skipped",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::target::Target;
+  use crate::testing::tree;
+  use std::fs;
+  use std::path::Path;
+
+  /// A workspace whose packages `a` (a library for Node.js 16) and `b` (an
+  /// app for Node.js 20) hold the extra `files`.
+  fn workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let mut files = files.to_vec();
+    files.extend([
+      ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+      (
+        "packages/a/package.json",
+        r#"{ "main": "dist/index.js", "engines": { "node": ">=16" } }"#,
+      ),
+      (
+        "packages/b/package.json",
+        r#"{ "private": true, "engines": { "node": ">=20" } }"#,
+      ),
+    ]);
+    tree(&format!("batch-{}", name), &files)
+  }
+
+  fn args(root: &Path) -> CliArgs {
+    CliArgs {
+      project_name: Some(root.to_string_lossy().into_owned()),
+      yes: true,
+      ..Default::default()
+    }
+  }
+
+  fn tsconfig(root: &Path, package: &str) -> serde_json::Value {
+    let path = root.join("packages").join(package).join("tsconfig.json");
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn writes_every_package() {
+    let root = workspace("init", &[]);
+    init_workspace(args(&root)).unwrap();
+
+    let a = tsconfig(&root, "a");
+    assert_eq!(a["compilerOptions"]["target"], "es2021");
+    assert_eq!(a["compilerOptions"]["composite"], true);
+    assert_eq!(a["compilerOptions"]["declaration"], true);
+    let b = tsconfig(&root, "b");
+    assert_eq!(b["compilerOptions"]["target"], "es2023");
+  }
+
+  #[test]
+  fn keeps_a_chosen_target() {
+    let root = workspace("target", &[]);
+    let workspace = Workspace::discover(&root).unwrap().unwrap();
+    let args = args(&root);
+    let mut shared = prompt_options(&args).unwrap();
+    assert!(!shared.target_chosen);
+
+    let detected = package_options(&workspace, &workspace.packages[0], &shared, &args).unwrap();
+    assert_eq!(detected.target, Target::Es2021);
+
+    // As if picked at the prompt
+    shared.target = Target::Es2017;
+    shared.target_chosen = true;
+    let chosen = package_options(&workspace, &workspace.packages[0], &shared, &args).unwrap();
+    assert_eq!(chosen.target, Target::Es2017);
+  }
+
+  #[test]
+  fn summarizes_each_package() {
+    let root = workspace("summary", &[]);
+    let workspace = Workspace::discover(&root).unwrap().unwrap();
+    let args = args(&root);
+    let shared = prompt_options(&args).unwrap();
+    let options: Vec<ProjectOptions> = workspace
+      .packages
+      .iter()
+      .map(|package| package_options(&workspace, package, &shared, &args).unwrap())
+      .collect();
+    let results = vec![
+      Ok(Written::Created),
+      Err(Error::new(ErrorCode::Json, "invalid tsconfig.json")),
+    ];
+
+    assert_eq!(
+      summary(&workspace, &options, &args, &results),
+      "Package     Kind                        Result\n\
+       packages/a  library, node, es2021       created\n\
+       packages/b  app, node, es2023           failed: invalid tsconfig.json\n"
+    );
+  }
+
+  #[test]
+  fn a_failing_package_does_not_stop_the_others() {
+    let root = workspace("failure", &[("packages/a/tsconfig.json", "{ not json")]);
+    let error = init_workspace(CliArgs {
+      merge: true,
+      ..args(&root)
+    })
+    .unwrap_err();
+
+    assert_eq!(error.code(), ErrorCode::Json);
+    assert_eq!(error.to_string(), "1 of 2 packages failed");
+    assert_eq!(
+      fs::read_to_string(root.join("packages/a/tsconfig.json")).unwrap(),
+      "{ not json"
+    );
+    assert_eq!(tsconfig(&root, "b")["compilerOptions"]["target"], "es2023");
+  }
+}
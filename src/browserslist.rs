@@ -0,0 +1,352 @@
+//! Derives the lowest safe ECMAScript target for a browser project from its
+//! browserslist config, using a compatibility table bundled with the crate
+//! instead of caniuse data.
+
+use crate::node;
+use crate::target::{Target, TARGETS};
+use crate::workspace;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The first version of each browser that supports every feature of
+/// es2017 through es2024, in `TARGETS` order (`esnext` is never assumed).
+const BROWSER_SUPPORT: &[(&[&str], [f64; 8])] = &[
+  (
+    &["chrome", "and_chr", "chromeandroid"],
+    [58.0, 64.0, 73.0, 80.0, 85.0, 94.0, 110.0, 119.0],
+  ),
+  (
+    &["edge"],
+    [16.0, 79.0, 79.0, 80.0, 85.0, 94.0, 110.0, 119.0],
+  ),
+  (
+    &["firefox", "ff", "and_ff", "firefoxandroid"],
+    [52.0, 78.0, 78.0, 78.0, 79.0, 93.0, 115.0, 128.0],
+  ),
+  // Regular expression lookbehind only arrived in 16.4
+  (
+    &["safari", "ios_saf", "ios"],
+    [11.0, 16.4, 16.4, 16.4, 16.4, 16.4, 16.4, 17.4],
+  ),
+  (
+    &["opera"],
+    [45.0, 51.0, 60.0, 67.0, 71.0, 80.0, 96.0, 105.0],
+  ),
+  (&["samsung"], [7.0, 9.0, 11.0, 13.0, 14.0, 17.0, 21.0, 25.0]),
+];
+
+/// Browsers without support for es2017 in any version.
+const LEGACY_BROWSERS: &[&str] = &["ie", "explorer", "op_mini", "operamini", "kaios"];
+
+/// Firefox ESR at the time the table was last updated.
+const FIREFOX_ESR: f64 = 115.0;
+
+/// The oldest version of each browser `defaults` (`> 0.5%, last 2 versions,
+/// Firefox ESR, not dead`) selected when the table was last updated. Opera
+/// Mini is left out, as it runs the scripts of a page on Opera's servers.
+const DEFAULTS: &[&str] = &[
+  "chrome 109",
+  "and_chr 131",
+  "android 131",
+  "edge 130",
+  "firefox 115",
+  "and_ff 132",
+  "safari 17.6",
+  "ios_saf 15.6",
+  "opera 113",
+  "samsung 25",
+];
+
+/// Queries from the first browserslist config found in `dir` or its parents.
+#[derive(Debug)]
+pub struct Browserslist {
+  pub path: PathBuf,
+  pub queries: Vec<String>,
+}
+
+/// The target a browserslist allows and what could not be taken into
+/// account along the way.
+#[derive(Debug)]
+pub struct Resolved {
+  pub target: Target,
+  pub warnings: Vec<String>,
+}
+
+/// Looks for `.browserslistrc` or a `browserslist` key in `package.json`,
+/// from `dir` upwards like browserslist itself.
+pub fn find(dir: &Path) -> Option<Browserslist> {
+  for dir in dir.ancestors() {
+    if let Ok(text) = fs::read_to_string(dir.join(".browserslistrc")) {
+      return Some(Browserslist {
+        path: dir.join(".browserslistrc"),
+        queries: rc_queries(&text),
+      });
+    }
+    let manifest = workspace::manifest(dir);
+    if let Some(queries) = manifest.and_then(|m| manifest_queries(&m["browserslist"])) {
+      return Some(Browserslist {
+        path: dir.join("package.json"),
+        queries,
+      });
+    }
+  }
+  None
+}
+
+/// The queries of a `.browserslistrc`, outside of any `[environment]`
+/// section or in `[production]`, the environment browserslist defaults to.
+fn rc_queries(text: &str) -> Vec<String> {
+  let mut queries = Vec::new();
+  let mut in_section = true;
+  for line in text.lines() {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+      in_section = section.split_whitespace().any(|env| env == "production");
+    } else if in_section {
+      queries.extend(split_queries(line));
+    }
+  }
+  queries
+}
+
+/// The `browserslist` value of a `package.json`: a query string, a list of
+/// them, or an object keyed by environment.
+fn manifest_queries(value: &Value) -> Option<Vec<String>> {
+  match value {
+    Value::String(query) => Some(split_queries(query)),
+    Value::Array(queries) => Some(
+      queries
+        .iter()
+        .filter_map(Value::as_str)
+        .flat_map(split_queries)
+        .collect(),
+    ),
+    Value::Object(envs) => envs
+      .get("production")
+      .or_else(|| envs.get("defaults"))
+      .and_then(manifest_queries),
+    _ => None,
+  }
+}
+
+fn split_queries(text: &str) -> Vec<String> {
+  text
+    .split(',')
+    .flat_map(|query| query.split(" or "))
+    .map(str::trim)
+    .filter(|query| !query.is_empty())
+    .map(str::to_string)
+    .collect()
+}
+
+/// The newest target every browser the queries select supports, never
+/// below es2017.
+pub fn resolve(queries: &[String]) -> Resolved {
+  let mut target = Target::EsNext;
+  let mut warnings = Vec::new();
+  let mut legacy = Vec::new();
+
+  for query in queries {
+    let query_lower = query.to_lowercase();
+    // Exclusions can only raise the lowest browser version
+    if query_lower.starts_with("not ") {
+      continue;
+    }
+    match query_target(&query_lower) {
+      Some(Ok(query_target)) => target = target.min(query_target),
+      Some(Err(())) => legacy.push(query.clone()),
+      None => {
+        warnings.push(format!(
+          "`{}` needs usage data to resolve, assuming it includes browsers without es2018",
+          query
+        ));
+        target = target.min(Target::Es2017);
+      }
+    }
+  }
+
+  if !legacy.is_empty() {
+    warnings.push(format!(
+      "{} predates es2017, targeting es2017 anyway; have the bundler downlevel further",
+      legacy.join(", ")
+    ));
+    target = Target::Es2017;
+  }
+  if target == Target::EsNext {
+    // Nothing but exclusions, which browserslist applies to `defaults`
+    return resolve(&["defaults".to_string()]);
+  }
+  Resolved { target, warnings }
+}
+
+/// The target a single query allows: `Some(Err(()))` if it selects a browser
+/// older than es2017, `None` if it cannot be resolved offline.
+fn query_target(query: &str) -> Option<Result<Target, ()>> {
+  if query.contains(" and ") {
+    // An intersection selects no more than any one of its sides does
+    let sides: Vec<Result<Target, ()>> = query
+      .split(" and ")
+      .filter(|side| !side.trim_start().starts_with("not "))
+      .filter_map(query_target)
+      .collect();
+    let narrowest = sides.iter().filter_map(|side| side.ok()).max();
+    return narrowest.map(Ok).or(sides.first().copied());
+  }
+  if query == "defaults" {
+    return DEFAULTS
+      .iter()
+      .filter_map(|query| query_target(query)?.ok())
+      .min()
+      .map(Ok);
+  }
+
+  let mut words = query.split_whitespace();
+  let browser = words.next()?;
+  let rest: Vec<&str> = words.collect();
+
+  if browser == "node" {
+    let major = node::parse_major(rest.last()?)?;
+    return Some(Ok(node::support(major).target));
+  }
+  if LEGACY_BROWSERS.contains(&browser) {
+    return Some(Err(()));
+  }
+
+  let version = match rest.as_slice() {
+    ["esr"] if browser == "firefox" || browser == "ff" => FIREFOX_ESR,
+    [">=" | ">", version] => parse_version(version)?,
+    // `safari 15-16.4` or `chrome 90`
+    [version] => parse_version(version.split('-').next()?)?,
+    _ => return None,
+  };
+  if browser == "android" {
+    // Android's WebView took Chrome's version numbers from 37 on
+    return Some(if version >= 37.0 {
+      support_target(&BROWSER_SUPPORT[0].1, version)
+    } else {
+      Err(())
+    });
+  }
+
+  let (_, versions) = BROWSER_SUPPORT
+    .iter()
+    .find(|(names, _)| names.contains(&browser))?;
+  Some(support_target(versions, version))
+}
+
+fn support_target(versions: &[f64; 8], version: f64) -> Result<Target, ()> {
+  versions
+    .iter()
+    .rposition(|&since| version >= since)
+    .map(|idx| TARGETS[idx])
+    .ok_or(())
+}
+
+fn parse_version(version: &str) -> Option<f64> {
+  version.trim_start_matches(['>', '=']).parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn resolve_all(queries: &[&str]) -> Resolved {
+    resolve(&queries.iter().map(|q| q.to_string()).collect::<Vec<_>>())
+  }
+
+  #[test]
+  fn query_target_reads_versions() {
+    assert_eq!(query_target("chrome 90"), Some(Ok(Target::Es2021)));
+    assert_eq!(query_target("chrome >= 110"), Some(Ok(Target::Es2023)));
+    assert_eq!(query_target("safari 15-16.4"), Some(Ok(Target::Es2017)));
+    assert_eq!(query_target("firefox esr"), Some(Ok(Target::Es2023)));
+    assert_eq!(query_target("node 18"), Some(Ok(Target::Es2022)));
+    assert_eq!(query_target("last 2 versions"), None);
+  }
+
+  #[test]
+  fn query_target_maps_android_to_chrome() {
+    assert_eq!(query_target("android 100"), Some(Ok(Target::Es2022)));
+    assert_eq!(query_target("android 4.4"), Some(Err(())));
+  }
+
+  #[test]
+  fn query_target_narrows_intersections() {
+    assert_eq!(
+      query_target("chrome 90 and firefox 115"),
+      Some(Ok(Target::Es2023))
+    );
+    assert_eq!(
+      query_target("chrome >= 100 and not dead"),
+      Some(Ok(Target::Es2022))
+    );
+    assert_eq!(query_target("> 0.5% and last 2 versions"), None);
+  }
+
+  #[test]
+  fn defaults_resolve_to_the_oldest_default_browser() {
+    // iOS Safari 15.6 lacks regular expression lookbehind
+    assert_eq!(query_target("defaults"), Some(Ok(Target::Es2017)));
+    let resolved = resolve_all(&["not ie 11"]);
+    assert_eq!(resolved.target, Target::Es2017);
+    assert!(resolved.warnings.is_empty());
+  }
+
+  #[test]
+  fn resolve_takes_the_oldest_browser() {
+    let resolved = resolve_all(&["Chrome 110", "safari 17.4", "not chrome 90"]);
+    assert_eq!(resolved.target, Target::Es2023);
+    assert!(resolved.warnings.is_empty());
+  }
+
+  #[test]
+  fn resolve_warns_about_usage_and_legacy_browsers() {
+    let resolved = resolve_all(&["> 0.5%", "chrome 120"]);
+    assert_eq!(resolved.target, Target::Es2017);
+    assert_eq!(resolved.warnings.len(), 1);
+    assert!(resolved.warnings[0].starts_with("`> 0.5%` needs usage data"));
+
+    let resolved = resolve_all(&["chrome 120", "IE 11"]);
+    assert_eq!(resolved.target, Target::Es2017);
+    assert_eq!(
+      resolved.warnings,
+      ["IE 11 predates es2017, targeting es2017 anyway; have the bundler downlevel further"]
+    );
+  }
+
+  #[test]
+  fn rc_queries_use_the_production_section() {
+    let text = "# browsers\nchrome 100, firefox 115 # main\n\
+                [development]\nlast 1 chrome version\n\
+                [production staging]\nsafari 17 or edge 120\n";
+    assert_eq!(
+      rc_queries(text),
+      ["chrome 100", "firefox 115", "safari 17", "edge 120"]
+    );
+  }
+
+  #[test]
+  fn manifest_queries_accept_every_shape() {
+    assert_eq!(
+      manifest_queries(&json!("chrome 100, firefox 115")),
+      Some(vec!["chrome 100".to_string(), "firefox 115".to_string()])
+    );
+    assert_eq!(
+      manifest_queries(&json!(["chrome 100", "safari 17 or edge 120"])),
+      Some(vec![
+        "chrome 100".to_string(),
+        "safari 17".to_string(),
+        "edge 120".to_string()
+      ])
+    );
+    assert_eq!(
+      manifest_queries(
+        &json!({ "development": ["last 1 chrome version"], "production": ["chrome 100"] })
+      ),
+      Some(vec!["chrome 100".to_string()])
+    );
+    assert_eq!(manifest_queries(&json!(true)), None);
+  }
+}
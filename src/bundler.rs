@@ -0,0 +1,174 @@
+//! The bundler that builds a project when tsc only type checks it, and the
+//! module resolution and types that go with it.
+
+use crate::module_format::ModuleFormat;
+use crate::runtime::Runtime;
+use crate::workspace;
+use crate::ProjectOptions;
+use serde_json::{json, Value};
+use std::path::Path;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Bundler {
+  Vite,
+  Esbuild,
+  Webpack,
+  Rollup,
+  Tsup,
+  Bun,
+}
+
+pub const BUNDLERS: &[Bundler] = &[
+  Bundler::Vite,
+  Bundler::Esbuild,
+  Bundler::Webpack,
+  Bundler::Rollup,
+  Bundler::Tsup,
+  Bundler::Bun,
+];
+
+impl Bundler {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Bundler::Vite => "vite",
+      Bundler::Esbuild => "esbuild",
+      Bundler::Webpack => "webpack",
+      Bundler::Rollup => "rollup",
+      Bundler::Tsup => "tsup",
+      Bundler::Bun => "bun",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Bundler> {
+    BUNDLERS
+      .iter()
+      .copied()
+      .find(|bundler| bundler.as_str() == name)
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      Bundler::Vite => "Vite",
+      Bundler::Esbuild => "esbuild",
+      Bundler::Webpack => "webpack",
+      Bundler::Rollup => "Rollup",
+      Bundler::Tsup => "tsup",
+      Bundler::Bun => "Bun",
+    }
+  }
+
+  /// Whether it resolves imports written with a `.ts` extension. webpack
+  /// and Rollup only do with extra loader or plugin configuration.
+  fn resolves_ts_extensions(&self) -> bool {
+    !matches!(self, Bundler::Webpack | Bundler::Rollup)
+  }
+
+  /// The `build` script that bundles `src/index.ts`. tsup names the bundle
+  /// after its format, which has to match the `type` of a `format` package,
+  /// and esbuild would otherwise write a browser script exporting nothing.
+  pub fn build_command(&self, format: ModuleFormat) -> &'static str {
+    match self {
+      Bundler::Vite => "vite build",
+      Bundler::Esbuild if format == ModuleFormat::Cjs => {
+        "esbuild src/index.ts --bundle --format=cjs --platform=node --packages=external --outdir=dist"
+      }
+      Bundler::Esbuild => {
+        "esbuild src/index.ts --bundle --format=esm --platform=node --packages=external --outdir=dist"
+      }
+      Bundler::Webpack => "webpack",
+      Bundler::Rollup => "rollup -c",
+      Bundler::Tsup if format == ModuleFormat::Cjs => "tsup src/index.ts",
+      Bundler::Tsup => "tsup src/index.ts --format esm",
+      Bundler::Bun => "bun build ./src/index.ts --outdir ./dist",
+    }
+  }
+
+  /// Where `build_command` writes the bundle, or `None` when the bundler's
+  /// own config decides, as Vite's, webpack's and Rollup's do. webpack also
+  /// needs one to load TypeScript and export anything at all.
+  pub fn bundle_file(&self) -> Option<&'static str> {
+    match self {
+      Bundler::Vite | Bundler::Webpack | Bundler::Rollup => None,
+      _ => Some("dist/index.js"),
+    }
+  }
+
+  pub fn compiler_options(&self) -> Value {
+    let mut compiler_options = json!({ "moduleResolution": "bundler" });
+    if self.resolves_ts_extensions() {
+      compiler_options["allowImportingTsExtensions"] = json!(true);
+    }
+    match self {
+      Bundler::Vite => compiler_options["types"] = json!(["vite/client"]),
+      Bundler::Bun => compiler_options["types"] = json!(["bun"]),
+      _ => {}
+    }
+    compiler_options
+  }
+}
+
+/// The bundler the `package.json` in `dir` depends on, or Bun for Bun
+/// projects that depend on none.
+pub fn detect(dir: &Path, runtime: Runtime) -> Option<Bundler> {
+  let manifest = workspace::manifest(dir).unwrap_or_default();
+  // tsup before esbuild, which it is built on
+  [
+    Bundler::Vite,
+    Bundler::Tsup,
+    Bundler::Esbuild,
+    Bundler::Webpack,
+    Bundler::Rollup,
+  ]
+  .into_iter()
+  .find(|bundler| workspace::depends_on(&manifest, &[bundler.as_str()]))
+  .or((runtime == Runtime::Bun).then_some(Bundler::Bun))
+}
+
+/// Combinations of answers that produce a config the bundler or tsc will
+/// not be happy with.
+pub fn warnings(options: &ProjectOptions) -> Vec<String> {
+  let Some(bundler) = options.bundler else {
+    return Vec::new();
+  };
+  let mut warnings = Vec::new();
+
+  if options.is_transpiler {
+    warnings.push(format!(
+      "tsc emits the JavaScript, so the {} settings were left out",
+      bundler.description()
+    ));
+    return warnings;
+  }
+  if options.runtime == Runtime::Deno {
+    warnings.push(format!(
+      "deno.json has no module resolution settings, configure {} separately",
+      bundler.description()
+    ));
+  }
+  if bundler == Bundler::Vite && !matches!(options.runtime, Runtime::Browser | Runtime::WebWorker) {
+    warnings.push(format!(
+      "vite/client types describe the browser, but the runtime is {}",
+      options.runtime.description()
+    ));
+  }
+  if bundler == Bundler::Bun && options.runtime != Runtime::Bun {
+    warnings.push(format!(
+      "bun types are added for Bun's bundler, but the code runs on {}",
+      options.runtime.description()
+    ));
+  }
+  if options.is_library && options.declaration_only.is_none() {
+    warnings.push(format!(
+      "with noEmit tsc writes no declaration files, have {} emit them or use --declaration-only",
+      bundler.description()
+    ));
+  }
+  if options.is_library && bundler.bundle_file().is_none() {
+    warnings.push(format!(
+      "{} decides where the bundle goes, point main and exports in package.json at it",
+      bundler.description()
+    ));
+  }
+  warnings
+}
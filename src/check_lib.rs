@@ -0,0 +1,504 @@
+//! Checks that a library is ready to publish: that the configs emitting it
+//! produce declarations under a fixed layout, that every entry point in
+//! `package.json` is a file they emit, and that npm packs their output.
+
+use crate::error::{Error, ErrorCode, Result};
+use crate::exports::{self, Entry};
+use crate::graph::normalize;
+use crate::jsonc;
+use crate::library_options;
+use crate::workspace::{relative, to_slash};
+use crate::write;
+use glob::Pattern;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Source extensions tsc emits each output extension from.
+const SOURCE_EXTENSIONS: &[(&str, &[&str])] = &[
+  (".d.ts", &[".ts", ".tsx"]),
+  (".d.mts", &[".mts"]),
+  (".d.cts", &[".cts"]),
+  (".js", &[".ts", ".tsx", ".js", ".jsx"]),
+  (".mjs", &[".mts", ".mjs"]),
+  (".cjs", &[".cts", ".cjs"]),
+];
+
+#[derive(Debug)]
+pub struct Problem {
+  pub message: String,
+  pub fix: String,
+}
+
+impl Problem {
+  fn new(message: impl Into<String>, fix: impl Into<String>) -> Self {
+    Problem {
+      message: message.into(),
+      fix: fix.into(),
+    }
+  }
+}
+
+/// A config that emits the library, with its path options relative to the
+/// package directory.
+struct Config {
+  name: String,
+  compiler_options: Map<String, Value>,
+}
+
+impl Config {
+  fn load(path: &Path, dir: &Path) -> Result<Config> {
+    if !path.is_file() {
+      return Err(Error::new(
+        ErrorCode::Io,
+        format!("{}: no such file", path.display()),
+      ));
+    }
+    Ok(Config {
+      name: relative(&to_slash(dir), &to_slash(&normalize(path))),
+      compiler_options: exports::compiler_options(path, dir, &read_config)?,
+    })
+  }
+
+  fn is_set(&self, key: &str) -> bool {
+    self.compiler_options.get(key) == Some(&json!(true))
+  }
+
+  fn path_option(&self, key: &str) -> Option<&str> {
+    self.compiler_options.get(key)?.as_str()
+  }
+
+  fn emits_declarations(&self) -> bool {
+    self.is_set("declaration") || self.is_set("composite")
+  }
+
+  /// The directory declarations (with `types`) or JavaScript go into.
+  fn out_dir(&self, types: bool) -> Option<&str> {
+    let out_dir = self.path_option("outDir");
+    if types {
+      self.path_option("declarationDir").or(out_dir)
+    } else {
+      out_dir
+    }
+  }
+}
+
+/// The configs to check in `dir` when none are named: those of a dual
+/// build if it has them, else its `tsconfig.json`.
+pub fn default_configs(dir: &Path) -> Vec<PathBuf> {
+  let dual = [dir.join("tsconfig.esm.json"), dir.join("tsconfig.cjs.json")];
+  if dual.iter().all(|config| config.is_file()) {
+    dual.to_vec()
+  } else {
+    vec![dir.join("tsconfig.json")]
+  }
+}
+
+/// Checks the library built by `configs` against the `package.json` next to
+/// the first one, failing with every problem found and how to fix it.
+pub fn run(configs: &[PathBuf]) -> Result<()> {
+  let dir = configs[0].parent().map(normalize).unwrap_or_default();
+  let problems = check(&dir, configs)?;
+  if problems.is_empty() {
+    println!("{} is ready to publish", dir.display());
+    return Ok(());
+  }
+
+  let problems: Vec<String> = problems
+    .iter()
+    .map(|problem| format!("{}\n    fix: {}", problem.message, problem.fix))
+    .collect();
+  Err(Error::new(
+    ErrorCode::Library,
+    format!(
+      "{} would not publish what its package.json points to:\n  {}",
+      dir.display(),
+      problems.join("\n  ")
+    ),
+  ))
+}
+
+pub fn check(dir: &Path, configs: &[PathBuf]) -> Result<Vec<Problem>> {
+  let configs = configs
+    .iter()
+    .map(|path| Config::load(path, dir))
+    .collect::<Result<Vec<_>>>()?;
+  let mut problems = Vec::new();
+  for config in &configs {
+    problems.extend(config_problems(config));
+  }
+
+  let manifest_path = dir.join("package.json");
+  let Ok(text) = fs::read_to_string(&manifest_path) else {
+    problems.push(Problem::new(
+      "there is no package.json",
+      "run `tsconfig-init --library` to create one",
+    ));
+    return Ok(problems);
+  };
+  let manifest = jsonc::Document::parse(&text)
+    .map_err(|e| Error::new(e.code(), format!("{}: {}", manifest_path.display(), e)))?;
+  problems.extend(entry_problems(&configs, &manifest, dir));
+  Ok(problems)
+}
+
+/// What stops `config` from emitting a library, from the options
+/// `generate_tsconfig` gives libraries.
+fn config_problems(config: &Config) -> Vec<Problem> {
+  if config.is_set("noEmit") {
+    return vec![Problem::new(
+      format!(
+        "{} sets noEmit, so tsc emits nothing to publish",
+        config.name
+      ),
+      format!(
+        "remove noEmit from {}, or set \"emitDeclarationOnly\": true instead if a bundler emits the JavaScript",
+        config.name
+      ),
+    )];
+  }
+
+  let mut problems = Vec::new();
+  // Only the declarations are emitted when a bundler emits the JavaScript
+  let declaration_only = config.is_set("emitDeclarationOnly");
+  if config.out_dir(declaration_only).is_none() {
+    let key = if declaration_only {
+      "declarationDir"
+    } else {
+      "outDir"
+    };
+    problems.push(Problem::new(
+      format!(
+        "{} has no {}, so the output is written next to the sources",
+        config.name, key
+      ),
+      format!("set \"{}\": \"dist\" in {}", key, config.name),
+    ));
+  }
+  for (key, value) in library_options().as_object().unwrap() {
+    let (is_set, consequence) = match key.as_str() {
+      "declaration" => (
+        config.emits_declarations(),
+        "no .d.ts files are emitted for TypeScript users",
+      ),
+      "rootDir" => (
+        config.path_option("rootDir").is_some(),
+        "where files land under outDir depends on which files are included",
+      ),
+      _ => (config.compiler_options.get(key) == Some(value), ""),
+    };
+    if !is_set {
+      // Files included from outside of rootDir fail the build
+      let include = match key.as_str() {
+        "rootDir" => format!(" along with \"include\": [{}]", value),
+        _ => String::new(),
+      };
+      problems.push(Problem::new(
+        format!("{} does not set {}, so {}", config.name, key, consequence),
+        format!("set \"{}\": {}{} in {}", key, value, include, config.name),
+      ));
+    }
+  }
+  problems
+}
+
+/// An entry point `package.json` names, such as `main` or a condition of
+/// `exports`.
+struct Target {
+  field: String,
+  path: String,
+  types: bool,
+}
+
+/// What is wrong with the entry points of `manifest`: missing fields, files
+/// the `configs` do not emit, conditions in the wrong order and output npm
+/// would not pack.
+fn entry_problems(configs: &[Config], manifest: &jsonc::Document, dir: &Path) -> Vec<Problem> {
+  let value = manifest.value();
+  let mut problems = Vec::new();
+  let mut targets = Vec::new();
+  for (field, types) in [("main", false), ("types", true), ("typings", true)] {
+    if let Some(path) = value[field].as_str() {
+      targets.push(Target {
+        field: field.to_string(),
+        path: path.to_string(),
+        types,
+      });
+    }
+  }
+  if let Some(exports) = value.get("exports") {
+    collect_exports(
+      manifest,
+      &mut vec!["exports"],
+      exports,
+      &mut targets,
+      &mut problems,
+    );
+  }
+
+  let out_dir = configs
+    .iter()
+    .find_map(|config| config.path_option("outDir"))
+    .unwrap_or("dist");
+  let index = configs
+    .iter()
+    .find_map(|config| Entry::emitted(&config.compiler_options, None));
+  if targets.iter().all(|target| target.types) {
+    let example = index
+      .as_ref()
+      .and_then(|index| index.js.clone())
+      .unwrap_or(format!("./{}/index.js", out_dir));
+    problems.push(Problem::new(
+      "package.json has no main or exports, so the package cannot be imported",
+      format!("add \"exports\": {{ \".\": \"{}\" }}", example),
+    ));
+  }
+  if !targets.iter().any(|target| target.types) {
+    let example = index
+      .and_then(|index| index.types)
+      .unwrap_or(format!("./{}/index.d.ts", out_dir));
+    problems.push(Problem::new(
+      "package.json has no types, so TypeScript users get no declarations",
+      format!(
+        "add \"types\": \"{}\", and a types condition to each export",
+        example
+      ),
+    ));
+  } else if value.get("exports").is_some()
+    && !targets
+      .iter()
+      .any(|t| t.types && t.field.starts_with("exports"))
+  {
+    problems.push(Problem::new(
+      "exports has no types condition, and TypeScript ignores the top-level types field of packages with exports under node16 and bundler resolution",
+      "add a types condition, listed first, next to each JavaScript file in exports",
+    ));
+  }
+
+  let mut unpacked: Vec<String> = Vec::new();
+  for target in &targets {
+    let path = target.path.trim_start_matches("./");
+    let emitted_by = configs.iter().find(|config| {
+      config
+        .out_dir(target.types)
+        .is_some_and(|out_dir| is_under(path, out_dir))
+    });
+    let Some(config) = emitted_by else {
+      let dirs: Vec<String> = configs
+        .iter()
+        .filter_map(|config| config.out_dir(target.types))
+        .map(|dir| format!("./{}", dir))
+        .collect();
+      if dirs.is_empty() {
+        continue;
+      }
+      let file_name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy());
+      problems.push(Problem::new(
+        format!(
+          "{} points to {}, which tsc does not emit: it writes to {}",
+          target.field,
+          target.path,
+          dirs.join(" and ")
+        ),
+        format!(
+          "point it into {}, e.g. {}/{}",
+          dirs[0],
+          dirs[0],
+          file_name.unwrap_or_default()
+        ),
+      ));
+      continue;
+    };
+
+    let out_dir = config.out_dir(target.types).unwrap();
+    if let Some(source) = missing_source(config, path, out_dir, dir) {
+      problems.push(Problem::new(
+        format!(
+          "{} points to {}, but there is no {} for tsc to emit it from",
+          target.field, target.path, source
+        ),
+        format!(
+          "add {}, or point it at the output of another file under ./{}",
+          source,
+          config.path_option("rootDir").unwrap_or(".")
+        ),
+      ));
+    }
+    if !unpacked.iter().any(|dir| dir == out_dir) && !is_packed(&value, path, dir) {
+      unpacked.push(out_dir.to_string());
+    }
+  }
+
+  for out_dir in unpacked {
+    problems.push(match value["files"].as_array() {
+      Some(_) => Problem::new(
+        format!(
+          "package.json files does not include ./{}, so npm leaves the output out",
+          out_dir
+        ),
+        format!("add \"{}\" to files", out_dir),
+      ),
+      None if dir.join(".npmignore").is_file() => Problem::new(
+        format!(".npmignore leaves ./{} out of the package", out_dir),
+        format!(
+          "remove {} from .npmignore, or list it in package.json files",
+          out_dir
+        ),
+      ),
+      None => Problem::new(
+        format!(
+          "without files or .npmignore npm skips what .gitignore ignores, including ./{}",
+          out_dir
+        ),
+        format!("add \"files\": [\"{}\"] to package.json", out_dir),
+      ),
+    });
+  }
+  problems
+}
+
+/// Collects the file paths of `exports` into `targets`, and a problem for
+/// every set of conditions that does not list `types` first and `default`
+/// last.
+fn collect_exports<'a>(
+  manifest: &jsonc::Document,
+  path: &mut Vec<&'a str>,
+  exports: &'a Value,
+  targets: &mut Vec<Target>,
+  problems: &mut Vec<Problem>,
+) {
+  match exports {
+    Value::String(file) => targets.push(Target {
+      field: exports::label(path),
+      path: file.clone(),
+      types: path.last() == Some(&"types") || is_declaration(file),
+    }),
+    Value::Array(fallbacks) => {
+      for fallback in fallbacks {
+        collect_exports(manifest, path, fallback, targets, problems);
+      }
+    }
+    Value::Object(object) => {
+      let keys = manifest.keys(path).unwrap_or_default();
+      let is_conditions = keys.iter().all(|key| !key.starts_with('.'));
+      if is_conditions {
+        let types = keys.iter().position(|key| key == "types");
+        let default = keys.iter().position(|key| key == "default");
+        if types.is_some_and(|types| types > 0) {
+          problems.push(Problem::new(
+            format!(
+              "{} lists types after {}, so TypeScript resolves the JavaScript file first",
+              exports::label(path),
+              keys[0]
+            ),
+            format!("move types to the top of {}", exports::label(path)),
+          ));
+        } else if default.is_some_and(|default| default + 1 < keys.len()) {
+          problems.push(Problem::new(
+            format!(
+              "{} lists conditions after default, which are never reached",
+              exports::label(path)
+            ),
+            format!("move default to the end of {}", exports::label(path)),
+          ));
+        }
+      }
+      for (key, value) in object {
+        path.push(key);
+        collect_exports(manifest, path, value, targets, problems);
+        path.pop();
+      }
+    }
+    _ => {}
+  }
+}
+
+fn is_declaration(path: &str) -> bool {
+  [".d.ts", ".d.mts", ".d.cts"]
+    .iter()
+    .any(|extension| path.ends_with(extension))
+}
+
+fn is_under(path: &str, dir: &str) -> bool {
+  dir == "." || path.starts_with(&format!("{}/", dir))
+}
+
+/// The source file `config` would have to emit `path` from, if it does not
+/// exist. Paths with `*` patterns and configs without a `rootDir`, whose
+/// layout depends on every included file, are not checked.
+fn missing_source(config: &Config, path: &str, out_dir: &str, dir: &Path) -> Option<String> {
+  let root_dir = config.path_option("rootDir")?;
+  if path.contains('*') {
+    return None;
+  }
+  let relative = path
+    .strip_prefix(out_dir)
+    .unwrap_or(path)
+    .trim_start_matches('/');
+  let (output_extension, source_extensions) = SOURCE_EXTENSIONS
+    .iter()
+    .find(|(extension, _)| relative.ends_with(extension))?;
+  let stem = &relative[..relative.len() - output_extension.len()];
+  let source = |extension: &str| format!("{}/{}{}", root_dir, stem, extension);
+
+  let exists = source_extensions
+    .iter()
+    .any(|extension| dir.join(source(extension)).is_file());
+  (!exists).then(|| format!("./{}", source(source_extensions[0])))
+}
+
+/// Whether npm would pack `path`, going by the `files` field of `manifest`,
+/// else `.npmignore`, else `.gitignore`.
+fn is_packed(manifest: &Value, path: &str, dir: &Path) -> bool {
+  if let Some(files) = manifest["files"].as_array() {
+    return files.iter().filter_map(Value::as_str).any(|entry| {
+      let entry = entry.trim_start_matches("./").trim_end_matches('/');
+      covers(entry, path)
+    });
+  }
+
+  let ignore_file = [".npmignore", ".gitignore"]
+    .iter()
+    .map(|name| dir.join(name))
+    .find(|path| path.is_file());
+  let Some(text) = ignore_file.and_then(|path| fs::read_to_string(path).ok()) else {
+    return true;
+  };
+  !text
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+    .any(|line| covers(line.trim_start_matches('/').trim_end_matches('/'), path))
+}
+
+/// Whether the `files` or ignore pattern `entry` matches `path` or one of
+/// the directories it is in.
+fn covers(entry: &str, path: &str) -> bool {
+  let Ok(pattern) = Pattern::new(entry) else {
+    return false;
+  };
+  let mut prefix = String::new();
+  for part in path.split('/') {
+    if !prefix.is_empty() {
+      prefix.push('/');
+    }
+    prefix.push_str(part);
+    if pattern.matches(&prefix) {
+      return true;
+    }
+  }
+  false
+}
+
+/// The config at `path`, or `None` if there is none.
+fn read_config(path: &Path) -> Result<Option<Value>> {
+  let in_file = |e: Error| Error::new(e.code(), format!("{}: {}", path.display(), e));
+  let Some(text) = write::read_existing(path).map_err(in_file)? else {
+    return Ok(None);
+  };
+  Ok(Some(
+    jsonc::Document::parse(&text).map_err(in_file)?.value(),
+  ))
+}
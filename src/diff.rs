@@ -0,0 +1,27 @@
+use console::style;
+use similar::{ChangeTag, TextDiff};
+use std::path::Path;
+
+/// Prints a colored unified diff of the changes from `old` to `new`.
+pub fn print_diff(path: &Path, old: &str, new: &str) {
+  let diff = TextDiff::from_lines(old, new);
+  if diff.ratio() == 1.0 {
+    println!("{} is already up to date", path.display());
+    return;
+  }
+
+  println!("{}", style(format!("--- {}", path.display())).bold());
+  println!("{}", style(format!("+++ {}", path.display())).bold());
+  for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+    println!("{}", style(hunk.header()).cyan());
+    for change in hunk.iter_changes() {
+      let line = change.to_string_lossy();
+      let line = line.trim_end_matches('\n');
+      match change.tag() {
+        ChangeTag::Delete => println!("{}", style(format!("-{}", line)).red()),
+        ChangeTag::Insert => println!("{}", style(format!("+{}", line)).green()),
+        ChangeTag::Equal => println!(" {}", line),
+      }
+    }
+  }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::io;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Stable error codes, exposed to JavaScript as the `code` property of the
+/// thrown `Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+  /// The command line arguments could not be parsed.
+  InvalidArgs,
+  /// An interactive prompt failed or was interrupted.
+  Prompt,
+  /// Reading or writing a file failed.
+  Io,
+  /// A JSON document could not be parsed or serialized.
+  Json,
+  /// A file we would write already exists and replacing it was not allowed.
+  Exists,
+  /// Project references form a cycle or point at a missing project.
+  References,
+  /// A library's configs and `package.json` do not publish what they
+  /// point to.
+  Library,
+}
+
+impl ErrorCode {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ErrorCode::InvalidArgs => "ERR_INVALID_ARGS",
+      ErrorCode::Prompt => "ERR_PROMPT",
+      ErrorCode::Io => "ERR_IO",
+      ErrorCode::Json => "ERR_JSON",
+      ErrorCode::Exists => "ERR_EXISTS",
+      ErrorCode::References => "ERR_REFERENCES",
+      ErrorCode::Library => "ERR_LIBRARY",
+    }
+  }
+}
+
+impl AsRef<str> for ErrorCode {
+  fn as_ref(&self) -> &str {
+    self.as_str()
+  }
+}
+
+#[derive(Debug)]
+pub struct Error {
+  code: ErrorCode,
+  message: String,
+}
+
+impl Error {
+  pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+    Error {
+      code,
+      message: message.into(),
+    }
+  }
+
+  pub fn code(&self) -> ErrorCode {
+    self.code
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+  fn from(e: io::Error) -> Self {
+    Error::new(ErrorCode::Io, e.to_string())
+  }
+}
+
+impl From<dialoguer::Error> for Error {
+  fn from(e: dialoguer::Error) -> Self {
+    Error::new(ErrorCode::Prompt, e.to_string())
+  }
+}
+
+impl From<serde_json::Error> for Error {
+  fn from(e: serde_json::Error) -> Self {
+    Error::new(ErrorCode::Json, e.to_string())
+  }
+}
+
+impl From<clap::Error> for Error {
+  fn from(e: clap::Error) -> Self {
+    Error::new(ErrorCode::InvalidArgs, e.render().to_string().trim_end())
+  }
+}
+
+impl From<Error> for napi::Error<ErrorCode> {
+  fn from(e: Error) -> Self {
+    napi::Error::new(e.code, e.message)
+  }
+}
@@ -0,0 +1,366 @@
+//! The `exports` map of a library, worked out from where its configs emit
+//! the JavaScript and declarations of `src/index.ts`.
+
+use crate::bundler::Bundler;
+use crate::error::Result;
+use crate::graph::normalize;
+use crate::module_format::ModuleFormat;
+use crate::workspace::{relative, to_slash};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Compiler options holding paths, which resolve against the config that
+/// sets them.
+const PATH_OPTIONS: &[&str] = &["outDir", "rootDir", "declarationDir"];
+
+/// The files a config emits for `src/index.ts`, as `package.json` paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+  /// `None` when a bundler emits the JavaScript somewhere only its own
+  /// config says.
+  pub js: Option<String>,
+  /// `None` when the config emits no declarations.
+  pub types: Option<String>,
+}
+
+impl Entry {
+  /// The entry emitted with `compiler_options`, or `None` if they emit
+  /// nothing. With `emitDeclarationOnly` the JavaScript is `bundler`'s.
+  pub fn emitted(compiler_options: &Map<String, Value>, bundler: Option<Bundler>) -> Option<Entry> {
+    let is_set = |key: &str| compiler_options.get(key) == Some(&json!(true));
+    let path_option = |key: &str| compiler_options.get(key).and_then(Value::as_str);
+    if is_set("noEmit") {
+      return None;
+    }
+    let declarations = is_set("declaration") || is_set("composite");
+    let types_dir = path_option("declarationDir").or(path_option("outDir"));
+    let js = if is_set("emitDeclarationOnly") {
+      bundler
+        .and_then(|bundler| bundler.bundle_file())
+        .map(package_path)
+    } else {
+      Some(format!("{}/index.js", package_path(path_option("outDir")?)))
+    };
+
+    Some(Entry {
+      js,
+      types: types_dir
+        .filter(|_| declarations)
+        .map(|dir| format!("{}/index.d.ts", package_path(dir))),
+    })
+  }
+
+  /// The conditions that resolve to this entry, `types` first since Node.js
+  /// and TypeScript take the first condition that matches.
+  fn conditions(&self) -> Value {
+    let mut conditions = Map::new();
+    if let Some(types) = &self.types {
+      conditions.insert("types".to_string(), json!(types));
+    }
+    if let Some(js) = &self.js {
+      conditions.insert("default".to_string(), json!(js));
+    }
+    Value::Object(conditions)
+  }
+}
+
+/// The entry points of a library built with `format` by the config
+/// `files` written into `dir`, and `bundler` if tsc only emits its
+/// declarations: the entry `main` and `types` point to and the `exports`
+/// map.
+pub fn entry_points(
+  format: ModuleFormat,
+  bundler: Option<Bundler>,
+  dir: &Path,
+  config_file: &str,
+  files: &[(PathBuf, Value)],
+) -> Option<(Entry, Value)> {
+  let entry = |name: &str| Entry::emitted(&written_options(files, dir, &dir.join(name)), bundler);
+
+  match format {
+    ModuleFormat::Dual => {
+      let esm = entry("tsconfig.esm.json")?;
+      let cjs = entry("tsconfig.cjs.json")?;
+      let exports = json!({
+          ".": {
+              "import": esm.conditions(),
+              "require": cjs.conditions(),
+          }
+      });
+      Some((cjs, exports))
+    }
+    ModuleFormat::Esm | ModuleFormat::Cjs => {
+      let entry = entry(config_file)?;
+      let exports = json!({ ".": entry.conditions() });
+      Some((entry, exports))
+    }
+  }
+}
+
+/// The compiler options of the config at `path`, including those it
+/// inherits through relative `extends`, with path options relative to
+/// `dir`. `load` reads a config, giving `None` if there is none. Configs
+/// extended from packages are not followed.
+pub fn compiler_options(
+  path: &Path,
+  dir: &Path,
+  load: &dyn Fn(&Path) -> Result<Option<Value>>,
+) -> Result<Map<String, Value>> {
+  let path = normalize(path);
+  let Some(config) = load(&path)? else {
+    return Ok(Map::new());
+  };
+  let mut seen = vec![path.clone()];
+  inherited_options(&path, &config, &normalize(dir), load, &mut seen)
+}
+
+/// `compiler_options` of one of the config `files` written into `dir`,
+/// which need not be on disk yet.
+pub fn written_options(files: &[(PathBuf, Value)], dir: &Path, path: &Path) -> Map<String, Value> {
+  let load = |path: &Path| {
+    Ok(
+      files
+        .iter()
+        .find(|(file, _)| normalize(file) == path)
+        .map(|(_, config)| config.clone()),
+    )
+  };
+  compiler_options(path, dir, &load).unwrap_or_default()
+}
+
+fn inherited_options(
+  path: &Path,
+  config: &Value,
+  dir: &Path,
+  load: &dyn Fn(&Path) -> Result<Option<Value>>,
+  seen: &mut Vec<PathBuf>,
+) -> Result<Map<String, Value>> {
+  let config_dir = path.parent().unwrap_or(Path::new(""));
+  let extends: Vec<&str> = match &config["extends"] {
+    Value::String(extends) => vec![extends],
+    Value::Array(extends) => extends.iter().filter_map(Value::as_str).collect(),
+    _ => Vec::new(),
+  };
+
+  let mut compiler_options = Map::new();
+  for extends in extends {
+    if !extends.starts_with('.') && !Path::new(extends).is_absolute() {
+      continue;
+    }
+    // Like tsc, try the path as given before adding `.json`
+    let base = normalize(&config_dir.join(extends));
+    let with_json = PathBuf::from(format!("{}.json", base.display()));
+    let mut found = None;
+    for candidate in [base, with_json] {
+      if let Some(config) = load(&candidate)? {
+        found = Some((candidate, config));
+        break;
+      }
+    }
+    let Some((base, base_config)) = found else {
+      continue;
+    };
+    if seen.contains(&base) {
+      continue;
+    }
+    seen.push(base.clone());
+    compiler_options.extend(inherited_options(&base, &base_config, dir, load, seen)?);
+  }
+
+  for (key, value) in config["compilerOptions"].as_object().into_iter().flatten() {
+    let value = match value.as_str() {
+      Some(option) if PATH_OPTIONS.contains(&key.as_str()) => {
+        let option = normalize(&config_dir.join(option));
+        json!(relative(&to_slash(dir), &to_slash(&option)))
+      }
+      _ => value.clone(),
+    };
+    compiler_options.insert(key.clone(), value);
+  }
+  Ok(compiler_options)
+}
+
+/// `path` into `package.json` written the way JavaScript would access it,
+/// e.g. `exports["."]`.
+pub fn label(path: &[impl AsRef<str>]) -> String {
+  let mut label = path[0].as_ref().to_string();
+  for key in &path[1..] {
+    let key = key.as_ref();
+    if key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+      label.push('.');
+      label.push_str(key);
+    } else {
+      label.push_str(&format!("[{:?}]", key));
+    }
+  }
+  label
+}
+
+/// `path` relative to the package root, as `package.json` paths are written.
+fn package_path(path: &str) -> String {
+  format!("./{}", path.trim_start_matches("./").trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn files(dir: &Path, configs: &[(&str, Value)]) -> Vec<(PathBuf, Value)> {
+    configs
+      .iter()
+      .map(|(name, config)| (dir.join(name), config.clone()))
+      .collect()
+  }
+
+  fn entry(js: Option<&str>, types: Option<&str>) -> Entry {
+    Entry {
+      js: js.map(str::to_string),
+      types: types.map(str::to_string),
+    }
+  }
+
+  #[test]
+  fn single_format_exports_the_out_dir() {
+    let dir = Path::new("/project");
+    let files = files(
+      dir,
+      &[(
+        "tsconfig.json",
+        json!({ "compilerOptions": { "outDir": "./build/", "declaration": true } }),
+      )],
+    );
+    let (main, exports) =
+      entry_points(ModuleFormat::Esm, None, dir, "tsconfig.json", &files).unwrap();
+    assert_eq!(
+      main,
+      entry(Some("./build/index.js"), Some("./build/index.d.ts"))
+    );
+    assert_eq!(
+      exports,
+      json!({ ".": { "types": "./build/index.d.ts", "default": "./build/index.js" } })
+    );
+  }
+
+  #[test]
+  fn dual_follows_extends() {
+    let dir = Path::new("/project");
+    let files = files(
+      dir,
+      &[
+        (
+          "tsconfig.json",
+          json!({ "compilerOptions": { "outDir": "dist", "composite": true } }),
+        ),
+        (
+          "tsconfig.esm.json",
+          json!({ "extends": "./tsconfig.json", "compilerOptions": { "outDir": "dist/esm" } }),
+        ),
+        (
+          "tsconfig.cjs.json",
+          json!({ "extends": "./tsconfig.json", "compilerOptions": { "outDir": "dist/cjs" } }),
+        ),
+      ],
+    );
+    let (main, exports) =
+      entry_points(ModuleFormat::Dual, None, dir, "tsconfig.json", &files).unwrap();
+    assert_eq!(
+      main,
+      entry(Some("./dist/cjs/index.js"), Some("./dist/cjs/index.d.ts"))
+    );
+    assert_eq!(
+      exports,
+      json!({
+          ".": {
+              "import": { "types": "./dist/esm/index.d.ts", "default": "./dist/esm/index.js" },
+              "require": { "types": "./dist/cjs/index.d.ts", "default": "./dist/cjs/index.js" },
+          }
+      })
+    );
+  }
+
+  #[test]
+  fn declarations_only_take_the_bundle() {
+    let dir = Path::new("/project");
+    let files = files(
+      dir,
+      &[(
+        "tsconfig.json",
+        json!({ "compilerOptions": {
+            "declaration": true,
+            "emitDeclarationOnly": true,
+            "declarationDir": "types",
+        } }),
+      )],
+    );
+    let (main, _) = entry_points(
+      ModuleFormat::Esm,
+      Some(Bundler::Esbuild),
+      dir,
+      "tsconfig.json",
+      &files,
+    )
+    .unwrap();
+    assert_eq!(
+      main,
+      entry(Some("./dist/index.js"), Some("./types/index.d.ts"))
+    );
+
+    // Vite's config decides where the bundle goes
+    let (main, exports) = entry_points(
+      ModuleFormat::Esm,
+      Some(Bundler::Vite),
+      dir,
+      "tsconfig.json",
+      &files,
+    )
+    .unwrap();
+    assert_eq!(main, entry(None, Some("./types/index.d.ts")));
+    assert_eq!(exports, json!({ ".": { "types": "./types/index.d.ts" } }));
+  }
+
+  #[test]
+  fn compiler_options_follow_relative_extends() {
+    let dir = Path::new("/project/packages/a");
+    let files = files(
+      dir,
+      &[
+        (
+          "tsconfig.json",
+          json!({
+              "extends": ["@tsconfig/node20", "../../tsconfig.base", "./tsconfig.json"],
+              "compilerOptions": { "declaration": true },
+          }),
+        ),
+        (
+          "../../tsconfig.base.json",
+          json!({ "compilerOptions": { "strict": true, "outDir": "./lib/", "declaration": false } }),
+        ),
+      ],
+    );
+    assert_eq!(
+      Value::Object(written_options(&files, dir, &dir.join("tsconfig.json"))),
+      json!({ "strict": true, "outDir": "../../lib", "declaration": true })
+    );
+  }
+
+  #[test]
+  fn nothing_is_emitted_without_an_out_dir_or_with_no_emit() {
+    let dir = Path::new("/project");
+    let no_emit = files(
+      dir,
+      &[(
+        "tsconfig.json",
+        json!({ "compilerOptions": { "outDir": "dist", "noEmit": true } }),
+      )],
+    );
+    assert_eq!(
+      entry_points(ModuleFormat::Esm, None, dir, "tsconfig.json", &no_emit),
+      None
+    );
+    let no_out_dir = files(dir, &[("tsconfig.json", json!({ "compilerOptions": {} }))]);
+    assert_eq!(
+      entry_points(ModuleFormat::Esm, None, dir, "tsconfig.json", &no_out_dir),
+      None
+    );
+  }
+}
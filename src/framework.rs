@@ -0,0 +1,97 @@
+//! The UI framework a project renders with, which decides how JSX is
+//! compiled.
+
+use crate::workspace;
+use serde_json::{json, Value};
+use std::path::Path;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Framework {
+  React,
+  Preact,
+  Solid,
+  Vue,
+  Svelte,
+  None,
+}
+
+pub const FRAMEWORKS: &[Framework] = &[
+  Framework::React,
+  Framework::Preact,
+  Framework::Solid,
+  Framework::Vue,
+  Framework::Svelte,
+  Framework::None,
+];
+
+impl Framework {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Framework::React => "react",
+      Framework::Preact => "preact",
+      Framework::Solid => "solid",
+      Framework::Vue => "vue",
+      Framework::Svelte => "svelte",
+      Framework::None => "none",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Framework> {
+    FRAMEWORKS
+      .iter()
+      .copied()
+      .find(|framework| framework.as_str() == name)
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      Framework::React => "React",
+      Framework::Preact => "Preact",
+      Framework::Solid => "Solid",
+      Framework::Vue => "Vue",
+      Framework::Svelte => "Svelte",
+      Framework::None => "None",
+    }
+  }
+
+  /// The package whose dependency gives the framework away.
+  fn package(&self) -> Option<&'static str> {
+    match self {
+      Framework::React => Some("react"),
+      Framework::Preact => Some("preact"),
+      Framework::Solid => Some("solid-js"),
+      Framework::Vue => Some("vue"),
+      Framework::Svelte => Some("svelte"),
+      Framework::None => None,
+    }
+  }
+
+  /// Compiler options for the framework's JSX, or its own types for Svelte,
+  /// which has no JSX.
+  pub fn compiler_options(&self) -> Value {
+    match self {
+      Framework::React => json!({ "jsx": "react-jsx" }),
+      Framework::Preact => json!({ "jsx": "react-jsx", "jsxImportSource": "preact" }),
+      // Both compile JSX with their own Babel plugins after tsc
+      Framework::Solid => json!({ "jsx": "preserve", "jsxImportSource": "solid-js" }),
+      Framework::Vue => json!({ "jsx": "preserve", "jsxImportSource": "vue" }),
+      Framework::Svelte => json!({ "types": ["svelte"] }),
+      Framework::None => json!({}),
+    }
+  }
+}
+
+/// The framework the project in `dir` depends on, if any. Checked in
+/// `FRAMEWORKS` order but with React last, since Preact and Solid projects
+/// often pull it in for compatibility.
+pub fn detect(dir: &Path) -> Option<Framework> {
+  let manifest = workspace::manifest(dir)?;
+  let mut frameworks: Vec<Framework> = FRAMEWORKS[1..].to_vec();
+  frameworks.push(Framework::React);
+  frameworks.into_iter().find(|framework| {
+    framework
+      .package()
+      .is_some_and(|package| workspace::depends_on(&manifest, &[package]))
+  })
+}
@@ -0,0 +1,416 @@
+//! Follows the `references` between tsconfigs so reference cycles and
+//! missing projects are reported up front rather than by `tsc -b`, and
+//! exports the graph as DOT or Mermaid.
+
+use crate::error::{Error, ErrorCode, Result};
+use crate::jsonc;
+use crate::workspace::{relative, to_slash};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+  Dot,
+  Mermaid,
+}
+
+#[derive(Debug)]
+pub struct Project {
+  /// The config file, which may not exist if a reference points nowhere.
+  pub path: PathBuf,
+  pub exists: bool,
+  /// Indices of the referenced projects.
+  pub references: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct Graph {
+  /// Directory the project labels are relative to.
+  base: PathBuf,
+  pub projects: Vec<Project>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+  New,
+  OnStack,
+  Done,
+}
+
+impl Graph {
+  /// Loads `roots` and every config reachable from them through
+  /// `references`.
+  pub fn load(roots: &[PathBuf]) -> Result<Graph> {
+    let base = roots
+      .first()
+      .and_then(|root| root.parent())
+      .map(normalize)
+      .unwrap_or_default();
+    let mut graph = Graph {
+      base,
+      projects: Vec::new(),
+    };
+    let mut index = HashMap::new();
+    let mut queue = VecDeque::new();
+    for root in roots {
+      let (i, new) = graph.add(&mut index, normalize(root));
+      if new {
+        queue.push_back(i);
+      }
+    }
+
+    while let Some(i) = queue.pop_front() {
+      let path = graph.projects[i].path.clone();
+      let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+        Err(e) => return Err(e.into()),
+      };
+      graph.projects[i].exists = true;
+
+      let config = jsonc::Document::parse(&text)
+        .map_err(|e| Error::new(e.code(), format!("{}: {}", path.display(), e)))?
+        .value();
+      let dir = path.parent().unwrap_or(Path::new(""));
+      for reference in config["references"].as_array().into_iter().flatten() {
+        let Some(target) = reference["path"].as_str() else {
+          continue;
+        };
+        let (j, new) = graph.add(&mut index, config_path(&dir.join(target)));
+        graph.projects[i].references.push(j);
+        if new {
+          queue.push_back(j);
+        }
+      }
+    }
+    Ok(graph)
+  }
+
+  fn add(&mut self, index: &mut HashMap<PathBuf, usize>, path: PathBuf) -> (usize, bool) {
+    if let Some(&i) = index.get(&path) {
+      return (i, false);
+    }
+    self.projects.push(Project {
+      path: path.clone(),
+      exists: false,
+      references: Vec::new(),
+    });
+    index.insert(path, self.projects.len() - 1);
+    (self.projects.len() - 1, true)
+  }
+
+  /// Every reference cycle, each listed as the projects along it with the
+  /// first one repeated at the end.
+  pub fn cycles(&self) -> Vec<Vec<usize>> {
+    let mut visits = vec![Visit::New; self.projects.len()];
+    let mut stack = Vec::new();
+    let mut cycles = Vec::new();
+    for i in 0..self.projects.len() {
+      if visits[i] == Visit::New {
+        self.visit(i, &mut visits, &mut stack, &mut cycles);
+      }
+    }
+    cycles
+  }
+
+  fn visit(
+    &self,
+    i: usize,
+    visits: &mut [Visit],
+    stack: &mut Vec<usize>,
+    cycles: &mut Vec<Vec<usize>>,
+  ) {
+    visits[i] = Visit::OnStack;
+    stack.push(i);
+    for &j in &self.projects[i].references {
+      match visits[j] {
+        Visit::New => self.visit(j, visits, stack, cycles),
+        Visit::OnStack => {
+          let start = stack.iter().position(|&k| k == j).unwrap();
+          let mut cycle = stack[start..].to_vec();
+          cycle.push(j);
+          cycles.push(cycle);
+        }
+        Visit::Done => {}
+      }
+    }
+    stack.pop();
+    visits[i] = Visit::Done;
+  }
+
+  /// A description of every cycle and missing project, empty if `tsc -b`
+  /// can build the graph.
+  pub fn problems(&self) -> Vec<String> {
+    let mut problems = Vec::new();
+    for cycle in self.cycles() {
+      let labels: Vec<String> = cycle.iter().map(|&i| self.label(i)).collect();
+      problems.push(format!("reference cycle: {}", labels.join(" -> ")));
+    }
+    for (i, project) in self.projects.iter().enumerate() {
+      if project.exists {
+        continue;
+      }
+      let referrers: Vec<String> = self
+        .projects
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.references.contains(&i))
+        .map(|(j, _)| self.label(j))
+        .collect();
+      problems.push(format!(
+        "{} does not exist (referenced by {})",
+        project.path.display(),
+        if referrers.is_empty() {
+          "the command line".to_string()
+        } else {
+          referrers.join(", ")
+        }
+      ));
+    }
+    problems
+  }
+
+  /// A short name for a project: its directory relative to the first root,
+  /// or the config file itself when it is not named `tsconfig.json`.
+  pub fn label(&self, i: usize) -> String {
+    let path = &self.projects[i].path;
+    let (dir, file) = match path.file_name() {
+      Some(name) if name == "tsconfig.json" => (path.parent().unwrap_or(path), None),
+      name => (path.parent().unwrap_or(path), name),
+    };
+    let dir = relative(&to_slash(&self.base), &to_slash(dir));
+    match file {
+      Some(file) if dir == "." => file.to_string_lossy().into_owned(),
+      Some(file) => format!("{}/{}", dir, file.to_string_lossy()),
+      None => dir,
+    }
+  }
+
+  /// Fails with every problem in the graph.
+  pub fn check(&self) -> Result<()> {
+    let problems = self.problems();
+    if problems.is_empty() {
+      return Ok(());
+    }
+    Err(Error::new(
+      ErrorCode::References,
+      format!(
+        "`tsc -b` will not build these project references:\n  {}",
+        problems.join("\n  ")
+      ),
+    ))
+  }
+
+  pub fn export(&self, format: Format) -> String {
+    let mut out = String::new();
+    match format {
+      Format::Dot => {
+        let _ = writeln!(out, "digraph references {{");
+        for (i, project) in self.projects.iter().enumerate() {
+          let style = if project.exists {
+            ""
+          } else {
+            " [style=dashed, color=red]"
+          };
+          let _ = writeln!(out, "  {:?}{};", self.label(i), style);
+        }
+        for (i, project) in self.projects.iter().enumerate() {
+          for &j in &project.references {
+            let _ = writeln!(out, "  {:?} -> {:?};", self.label(i), self.label(j));
+          }
+        }
+        let _ = writeln!(out, "}}");
+      }
+      Format::Mermaid => {
+        let _ = writeln!(out, "graph TD");
+        for (i, project) in self.projects.iter().enumerate() {
+          let class = if project.exists { "" } else { ":::missing" };
+          let _ = writeln!(out, "  p{}[\"{}\"]{}", i, self.label(i), class);
+        }
+        for (i, project) in self.projects.iter().enumerate() {
+          for &j in &project.references {
+            let _ = writeln!(out, "  p{} --> p{}", i, j);
+          }
+        }
+        if self.projects.iter().any(|project| !project.exists) {
+          let _ = writeln!(out, "  classDef missing stroke:#f00,stroke-dasharray:4 4");
+        }
+      }
+    }
+    out
+  }
+}
+
+/// Warns about every problem in the graph reachable from `roots`, configs
+/// that were just written. They are there either way, so only the `graph`
+/// command fails on these.
+pub fn warn(roots: &[PathBuf]) {
+  let problems = match Graph::load(roots) {
+    Ok(graph) => graph.problems(),
+    Err(e) => vec![e.to_string()],
+  };
+  for problem in problems {
+    eprintln!("warning: `tsc -b` will not build this: {}", problem);
+  }
+}
+
+/// The config a reference points at: a `.json` file as is, otherwise the
+/// `tsconfig.json` in that directory.
+fn config_path(path: &Path) -> PathBuf {
+  let path = normalize(path);
+  if path.extension().is_some_and(|ext| ext == "json") {
+    path
+  } else {
+    path.join("tsconfig.json")
+  }
+}
+
+/// Resolves `.` and `..` without touching the file system.
+pub fn normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        if !normalized.pop() {
+          normalized.push("..");
+        }
+      }
+      component => normalized.push(component),
+    }
+  }
+  normalized
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::tree;
+
+  fn references(paths: &[&str]) -> String {
+    let references: Vec<String> = paths
+      .iter()
+      .map(|path| format!("{{ \"path\": \"{}\" }}", path))
+      .collect();
+    format!(
+      "{{\n  // built by tsc -b\n  \"references\": [{}],\n}}\n",
+      references.join(", ")
+    )
+  }
+
+  fn labels(graph: &Graph) -> Vec<String> {
+    (0..graph.projects.len()).map(|i| graph.label(i)).collect()
+  }
+
+  #[test]
+  fn follows_references() {
+    let dir = tree(
+      "graph-follows",
+      &[
+        (
+          "tsconfig.json",
+          &references(&["./a", "./b/tsconfig.build.json"]),
+        ),
+        (
+          "a/tsconfig.json",
+          &references(&["../b/tsconfig.build.json"]),
+        ),
+        ("b/tsconfig.build.json", "{}"),
+      ],
+    );
+    let graph = Graph::load(&[dir.join("tsconfig.json")]).unwrap();
+    assert_eq!(labels(&graph), [".", "a", "b/tsconfig.build.json"]);
+    assert_eq!(graph.projects[0].references, [1, 2]);
+    assert_eq!(graph.projects[1].references, [2]);
+    assert!(graph.cycles().is_empty());
+    assert!(graph.problems().is_empty());
+    assert!(graph.check().is_ok());
+  }
+
+  #[test]
+  fn finds_cycles() {
+    let dir = tree(
+      "graph-cycles",
+      &[
+        ("tsconfig.json", &references(&["./a"])),
+        ("a/tsconfig.json", &references(&["../b"])),
+        ("b/tsconfig.json", &references(&["../c"])),
+        ("c/tsconfig.json", &references(&["../a", "../c"])),
+      ],
+    );
+    let graph = Graph::load(&[dir.join("tsconfig.json")]).unwrap();
+    assert_eq!(graph.cycles(), [vec![1, 2, 3, 1], vec![3, 3]]);
+    assert_eq!(
+      graph.problems(),
+      [
+        "reference cycle: a -> b -> c -> a",
+        "reference cycle: c -> c"
+      ]
+    );
+    assert_eq!(graph.check().unwrap_err().code(), ErrorCode::References);
+  }
+
+  #[test]
+  fn finds_missing_projects() {
+    let dir = tree(
+      "graph-missing",
+      &[
+        ("tsconfig.json", &references(&["./a", "./gone"])),
+        ("a/tsconfig.json", &references(&["../gone"])),
+      ],
+    );
+    let graph = Graph::load(&[dir.join("tsconfig.json"), dir.join("extra.json")]).unwrap();
+    assert_eq!(labels(&graph), [".", "extra.json", "a", "gone"]);
+    assert!(!graph.projects[3].exists);
+    let problems = graph.problems();
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].ends_with("extra.json does not exist (referenced by the command line)"));
+    assert!(problems[1].ends_with("tsconfig.json does not exist (referenced by ., a)"));
+    assert_eq!(graph.check().unwrap_err().code(), ErrorCode::References);
+  }
+
+  #[test]
+  fn rejects_invalid_configs() {
+    let dir = tree("graph-invalid", &[("tsconfig.json", "{ \"references\": [")]);
+    let error = Graph::load(&[dir.join("tsconfig.json")]).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::Json);
+    assert!(error.to_string().contains("tsconfig.json: "));
+  }
+
+  #[test]
+  fn exports_dot_and_mermaid() {
+    let dir = tree(
+      "graph-export",
+      &[
+        ("tsconfig.json", &references(&["./a", "./gone"])),
+        ("a/tsconfig.json", "{}"),
+      ],
+    );
+    let graph = Graph::load(&[dir.join("tsconfig.json")]).unwrap();
+    assert_eq!(
+      graph.export(Format::Dot),
+      "digraph references {\n  \".\";\n  \"a\";\n  \"gone\" [style=dashed, color=red];\n  \".\" -> \"a\";\n  \".\" -> \"gone\";\n}\n"
+    );
+    assert_eq!(
+      graph.export(Format::Mermaid),
+      "graph TD\n  p0[\".\"]\n  p1[\"a\"]\n  p2[\"gone\"]:::missing\n  p0 --> p1\n  p0 --> p2\n  classDef missing stroke:#f00,stroke-dasharray:4 4\n"
+    );
+  }
+
+  #[test]
+  fn normalizes_without_the_file_system() {
+    assert_eq!(normalize(Path::new("a/./b/../c")), PathBuf::from("a/c"));
+    assert_eq!(normalize(Path::new("../a/..")), PathBuf::from(".."));
+    assert_eq!(
+      config_path(Path::new("a/b")),
+      PathBuf::from("a/b/tsconfig.json")
+    );
+    assert_eq!(
+      config_path(Path::new("a/b.json")),
+      PathBuf::from("a/b.json")
+    );
+  }
+}
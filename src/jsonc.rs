@@ -0,0 +1,723 @@
+//! A small JSONC (JSON with comments and trailing commas) reader and editor.
+//!
+//! Edits are applied to the original text rather than by re-serializing, so
+//! comments, key order and formatting outside of the edited values survive.
+
+use crate::error::{Error, ErrorCode, Result};
+use serde_json::{Map, Number, Value};
+use std::fmt;
+use std::ops::Range;
+
+/// A parsed JSONC document that can be edited in place.
+#[derive(Debug, Clone)]
+pub struct Document {
+  text: String,
+  root: Node,
+  style: Style,
+}
+
+/// The line ending and indentation unit new text is written with, taken
+/// from the document so edits blend in.
+#[derive(Debug, Clone)]
+struct Style {
+  newline: &'static str,
+  indent: String,
+}
+
+impl Style {
+  /// Uses CRLF if any line ends with it and the leading whitespace of the
+  /// first indented line, defaulting to `\n` and two spaces.
+  fn detect(text: &str) -> Self {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let indent = text
+      .lines()
+      .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+      .find(|indent| !indent.is_empty())
+      .map_or("  ", |indent| {
+        if indent.starts_with('\t') {
+          "\t"
+        } else {
+          indent
+        }
+      });
+    Style {
+      newline,
+      indent: indent.to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+  span: Range<usize>,
+  kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+  Object(Vec<Member>),
+  Array(Vec<Node>),
+  Scalar(Value),
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+  key: String,
+  /// From the opening quote of the key to the end of the value.
+  span: Range<usize>,
+  value: Node,
+}
+
+impl Document {
+  pub fn parse(text: &str) -> Result<Self> {
+    let mut parser = Parser {
+      text,
+      bytes: text.as_bytes(),
+      pos: 0,
+    };
+    if text.starts_with('\u{feff}') {
+      parser.pos = '\u{feff}'.len_utf8();
+    }
+    parser.skip_trivia()?;
+    let root = parser.parse_value()?;
+    parser.skip_trivia()?;
+    if parser.pos != text.len() {
+      return Err(parser.error("unexpected content after the end of the document"));
+    }
+
+    Ok(Document {
+      text: text.to_string(),
+      root,
+      style: Style::detect(text),
+    })
+  }
+
+  /// The document as plain JSON, without comments.
+  pub fn value(&self) -> Value {
+    self.root.to_value()
+  }
+
+  /// The keys of the object at `path` in document order, which `value`
+  /// does not keep.
+  pub fn keys(&self, path: &[&str]) -> Option<Vec<String>> {
+    match &self.find(path)?.kind {
+      NodeKind::Object(members) => Some(members.iter().map(|m| m.key.clone()).collect()),
+      _ => None,
+    }
+  }
+
+  /// Sets the value at `path`, creating any missing parent objects. Existing
+  /// values are replaced in place; new keys are appended to their object
+  /// using the indentation of its other members.
+  pub fn set(&mut self, path: &[&str], value: &Value) -> Result<()> {
+    let Some((key, parents)) = path.split_last() else {
+      self.text = to_jsonc(value, "", &self.style);
+      return self.reparse();
+    };
+
+    // Walk down as far as the path already exists
+    let mut depth = 0;
+    let mut node = &self.root;
+    while depth < parents.len() {
+      match node.member(parents[depth]) {
+        Some(member) => node = &member.value,
+        None => break,
+      }
+      depth += 1;
+    }
+    let NodeKind::Object(members) = &node.kind else {
+      return Err(Error::new(
+        ErrorCode::Json,
+        format!("cannot set {}: parent is not an object", path.join(".")),
+      ));
+    };
+
+    // Wrap the value in the objects that are missing along the way
+    let mut new_value = value.clone();
+    let mut new_key = *key;
+    for missing in parents[depth..].iter().rev() {
+      let mut wrapper = Map::new();
+      wrapper.insert(new_key.to_string(), new_value);
+      new_value = Value::Object(wrapper);
+      new_key = missing;
+    }
+
+    if let Some(existing) = members.iter().find(|m| m.key == new_key) {
+      let indent = self.indent_at(existing.span.start);
+      let replacement = to_jsonc(&new_value, indent, &self.style);
+      self
+        .text
+        .replace_range(existing.value.span.clone(), &replacement);
+    } else {
+      // Apply back to front so earlier positions stay valid
+      let mut edits = self.insertion(node, members, new_key, &new_value);
+      edits.sort_by_key(|(pos, _)| std::cmp::Reverse(*pos));
+      for (pos, text) in edits {
+        self.text.insert_str(pos, &text);
+      }
+    }
+    self.reparse()
+  }
+
+  /// Edits the document until it matches `value`, touching only the members
+  /// that changed. Arrays and scalars that differ are replaced whole.
+  pub fn update(&mut self, value: &Value) -> Result<()> {
+    self.update_at(&[], value)
+  }
+
+  fn update_at(&mut self, path: &[&str], value: &Value) -> Result<()> {
+    let current = self.find(path).map(Node::to_value);
+    let (Some(Value::Object(current)), Value::Object(target)) = (&current, value) else {
+      if current.as_ref() != Some(value) {
+        self.set(path, value)?;
+      }
+      return Ok(());
+    };
+
+    for key in current.keys().filter(|key| !target.contains_key(*key)) {
+      self.remove(&[path, &[key.as_str()]].concat())?;
+    }
+    for (key, value) in target {
+      self.update_at(&[path, &[key.as_str()]].concat(), value)?;
+    }
+    Ok(())
+  }
+
+  /// Removes the member at `path` along with its separating comma. Returns
+  /// whether anything was removed.
+  pub fn remove(&mut self, path: &[&str]) -> Result<bool> {
+    let Some((key, parents)) = path.split_last() else {
+      return Ok(false);
+    };
+    let Some(NodeKind::Object(members)) = self.find(parents).map(|node| &node.kind) else {
+      return Ok(false);
+    };
+    let Some(idx) = members.iter().position(|m| m.key == *key) else {
+      return Ok(false);
+    };
+
+    let member = &members[idx];
+    // Take the whole line when the member sits on its own line
+    let line_start = self.text[..member.span.start]
+      .rfind('\n')
+      .map_or(0, |i| i + 1);
+    let start = if self.text[line_start..member.span.start].trim().is_empty() {
+      line_start
+    } else {
+      member.span.start
+    };
+    let mut end = member.span.end;
+    match self.next_comma(end) {
+      Some(comma) => end = comma + 1,
+      None if idx > 0 => {
+        // Last member: drop the comma that followed the previous one instead
+        if let Some(comma) = self.next_comma(members[idx - 1].span.end) {
+          self.text.replace_range(comma..comma + 1, "");
+          return self.remove_range(start - 1, end - 1);
+        }
+      }
+      None => {}
+    }
+    self.remove_range(start, end)
+  }
+
+  fn remove_range(&mut self, start: usize, mut end: usize) -> Result<bool> {
+    // A comment trailing the member on its line goes with it, as does the
+    // line itself when nothing else is left on it
+    if let Some(newline) = self.text[end..].find('\n').map(|i| end + i) {
+      if self.is_trivia(end..newline) {
+        end = newline;
+        if start == 0 || self.text[..start].ends_with('\n') {
+          end += 1;
+        }
+      }
+    }
+    self.text.replace_range(start..end, "");
+    self.reparse()?;
+    Ok(true)
+  }
+
+  fn find(&self, path: &[&str]) -> Option<&Node> {
+    path
+      .iter()
+      .try_fold(&self.root, |node, key| node.member(key).map(|m| &m.value))
+  }
+
+  /// The insertions that append `key` to the object `node`.
+  fn insertion(
+    &self,
+    node: &Node,
+    members: &[Member],
+    key: &str,
+    value: &Value,
+  ) -> Vec<(usize, String)> {
+    let key = serde_json::to_string(key).unwrap();
+    let newline = self.style.newline;
+
+    let Some(last) = members.last() else {
+      let indent = self.indent_at(node.span.start);
+      let inner = format!("{}{}", indent, self.style.indent);
+      let entry = format!("{}: {}", key, to_jsonc(value, &inner, &self.style));
+      return vec![(
+        node.span.end - 1,
+        format!("{newline}{inner}{entry}{newline}{indent}"),
+      )];
+    };
+
+    let comma = self.next_comma(last.span.end);
+    if !self.text[node.span.clone()].contains('\n') {
+      let entry = format!("{}: {}", key, serde_json::to_string(value).unwrap());
+      return match comma {
+        Some(comma) => vec![(comma + 1, format!(" {},", entry))],
+        None => vec![(last.span.end, format!(", {}", entry))],
+      };
+    }
+
+    let indent = self.indent_at(last.span.start);
+    let entry = format!("{}: {}", key, to_jsonc(value, indent, &self.style));
+
+    // Go after anything trailing the last member on its line, such as a
+    // comment, and keep a trailing comma if the object already uses them
+    let after = comma.map_or(last.span.end, |comma| comma + 1);
+    let line_end = self.text[after..].find(newline).map(|i| after + i);
+    let at = match line_end {
+      Some(end) if end < node.span.end && self.is_trivia(after..end) => end,
+      _ => after,
+    };
+    match comma {
+      Some(_) => vec![(at, format!("{newline}{indent}{entry},"))],
+      // Listed first so that when both land at the same position the comma
+      // still ends up in front
+      None => vec![
+        (at, format!("{newline}{indent}{entry}")),
+        (last.span.end, ",".to_string()),
+      ],
+    }
+  }
+
+  /// Whether `range` only holds whitespace and comments.
+  fn is_trivia(&self, range: Range<usize>) -> bool {
+    let mut parser = Parser {
+      text: &self.text[..range.end],
+      bytes: &self.text.as_bytes()[..range.end],
+      pos: range.start,
+    };
+    parser.skip_trivia().is_ok() && parser.pos == range.end
+  }
+
+  /// The position of the comma following `pos`, skipping comments and
+  /// whitespace.
+  fn next_comma(&self, pos: usize) -> Option<usize> {
+    let mut parser = Parser {
+      text: &self.text,
+      bytes: self.text.as_bytes(),
+      pos,
+    };
+    parser.skip_trivia().ok()?;
+    (parser.peek() == Some(b',')).then_some(parser.pos)
+  }
+
+  /// The indentation of the line containing `pos`.
+  fn indent_at(&self, pos: usize) -> &str {
+    let line_start = self.text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line = &self.text[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+  }
+
+  fn reparse(&mut self) -> Result<()> {
+    *self = Document::parse(&self.text)?;
+    Ok(())
+  }
+}
+
+impl fmt::Display for Document {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.text)
+  }
+}
+
+/// Pretty prints `value` in the document's `style`, continuing lines at
+/// `indent` so it can be spliced into a document.
+fn to_jsonc(value: &Value, indent: &str, style: &Style) -> String {
+  let pretty = serde_json::to_string_pretty(value).unwrap();
+  // Strings never span lines, so leading spaces are all indentation, two
+  // per level
+  let lines: Vec<String> = pretty
+    .lines()
+    .map(|line| {
+      let content = line.trim_start_matches(' ');
+      let depth = (line.len() - content.len()) / 2;
+      format!("{}{}", style.indent.repeat(depth), content)
+    })
+    .collect();
+  lines.join(&format!("{}{}", style.newline, indent))
+}
+
+impl Node {
+  fn member(&self, key: &str) -> Option<&Member> {
+    match &self.kind {
+      NodeKind::Object(members) => members.iter().find(|m| m.key == key),
+      _ => None,
+    }
+  }
+
+  fn to_value(&self) -> Value {
+    match &self.kind {
+      NodeKind::Object(members) => Value::Object(
+        members
+          .iter()
+          .map(|m| (m.key.clone(), m.value.to_value()))
+          .collect(),
+      ),
+      NodeKind::Array(items) => Value::Array(items.iter().map(Node::to_value).collect()),
+      NodeKind::Scalar(value) => value.clone(),
+    }
+  }
+}
+
+struct Parser<'a> {
+  text: &'a str,
+  bytes: &'a [u8],
+  pos: usize,
+}
+
+impl Parser<'_> {
+  fn peek(&self) -> Option<u8> {
+    self.bytes.get(self.pos).copied()
+  }
+
+  fn error(&self, message: &str) -> Error {
+    let mut pos = self.pos.min(self.text.len());
+    while !self.text.is_char_boundary(pos) {
+      pos -= 1;
+    }
+    let line = self.text[..pos].matches('\n').count() + 1;
+    let column = pos - self.text[..pos].rfind('\n').map_or(0, |i| i + 1) + 1;
+    Error::new(
+      ErrorCode::Json,
+      format!("{} at line {} column {}", message, line, column),
+    )
+  }
+
+  /// Skips whitespace, `//` line comments and `/* */` block comments.
+  fn skip_trivia(&mut self) -> Result<()> {
+    loop {
+      match self.peek() {
+        Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+        Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'/') => {
+          self.pos = self.text[self.pos..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.pos + i);
+        }
+        Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'*') => {
+          match self.text[self.pos + 2..].find("*/") {
+            Some(i) => self.pos += i + 4,
+            None => return Err(self.error("unterminated block comment")),
+          }
+        }
+        _ => return Ok(()),
+      }
+    }
+  }
+
+  fn parse_value(&mut self) -> Result<Node> {
+    let start = self.pos;
+    let kind = match self.peek() {
+      Some(b'{') => self.parse_object()?,
+      Some(b'[') => self.parse_array()?,
+      Some(b'"') => NodeKind::Scalar(Value::String(self.parse_string()?)),
+      Some(b't') => self.parse_literal("true", Value::Bool(true))?,
+      Some(b'f') => self.parse_literal("false", Value::Bool(false))?,
+      Some(b'n') => self.parse_literal("null", Value::Null)?,
+      Some(b'-' | b'0'..=b'9') => self.parse_number()?,
+      Some(_) => return Err(self.error("expected a value")),
+      None => return Err(self.error("unexpected end of input")),
+    };
+
+    Ok(Node {
+      span: start..self.pos,
+      kind,
+    })
+  }
+
+  fn parse_object(&mut self) -> Result<NodeKind> {
+    self.pos += 1;
+    let mut members = Vec::new();
+    loop {
+      self.skip_trivia()?;
+      match self.peek() {
+        Some(b'}') => {
+          self.pos += 1;
+          return Ok(NodeKind::Object(members));
+        }
+        Some(b'"') => {}
+        _ => return Err(self.error("expected a string key or `}`")),
+      }
+
+      let start = self.pos;
+      let key = self.parse_string()?;
+      self.skip_trivia()?;
+      if self.peek() != Some(b':') {
+        return Err(self.error("expected `:`"));
+      }
+      self.pos += 1;
+      self.skip_trivia()?;
+      let value = self.parse_value()?;
+      members.push(Member {
+        key,
+        span: start..self.pos,
+        value,
+      });
+
+      self.skip_trivia()?;
+      match self.peek() {
+        Some(b',') => self.pos += 1,
+        Some(b'}') => {}
+        _ => return Err(self.error("expected `,` or `}`")),
+      }
+    }
+  }
+
+  fn parse_array(&mut self) -> Result<NodeKind> {
+    self.pos += 1;
+    let mut items = Vec::new();
+    loop {
+      self.skip_trivia()?;
+      if self.peek() == Some(b']') {
+        self.pos += 1;
+        return Ok(NodeKind::Array(items));
+      }
+      items.push(self.parse_value()?);
+
+      self.skip_trivia()?;
+      match self.peek() {
+        Some(b',') => self.pos += 1,
+        Some(b']') => {}
+        _ => return Err(self.error("expected `,` or `]`")),
+      }
+    }
+  }
+
+  fn parse_string(&mut self) -> Result<String> {
+    let start = self.pos;
+    self.pos += 1;
+    loop {
+      match self.peek() {
+        Some(b'"') => break,
+        // A trailing backslash must not step past the end of the text
+        Some(b'\\') => self.pos = (self.pos + 2).min(self.bytes.len()),
+        Some(b'\n') | None => return Err(self.error("unterminated string")),
+        Some(_) => self.pos += 1,
+      }
+    }
+    self.pos += 1;
+    serde_json::from_str(&self.text[start..self.pos]).map_err(|_| self.error("invalid string"))
+  }
+
+  fn parse_literal(&mut self, literal: &str, value: Value) -> Result<NodeKind> {
+    if !self.text[self.pos..].starts_with(literal) {
+      return Err(self.error("expected a value"));
+    }
+    self.pos += literal.len();
+    Ok(NodeKind::Scalar(value))
+  }
+
+  fn parse_number(&mut self) -> Result<NodeKind> {
+    let start = self.pos;
+    while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+      self.pos += 1;
+    }
+    self.text[start..self.pos]
+      .parse::<Number>()
+      .map(|n| NodeKind::Scalar(Value::Number(n)))
+      .map_err(|_| self.error("invalid number"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn edited(text: &str, edit: impl FnOnce(&mut Document) -> Result<()>) -> String {
+    let mut document = Document::parse(text).unwrap();
+    edit(&mut document).unwrap();
+    document.to_string()
+  }
+
+  fn parse_error(text: &str) -> String {
+    let error = Document::parse(text).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::Json);
+    error.to_string()
+  }
+
+  #[test]
+  fn parses_comments_and_trailing_commas() {
+    let document = Document::parse(
+      "\u{feff}// config\n{\n  /* a */ \"a\": [1, 2,],\n  \"b\": { \"c\": \"\\\"d\\\"\" }, // b\n}\n",
+    )
+    .unwrap();
+    assert_eq!(
+      document.value(),
+      json!({ "a": [1, 2], "b": { "c": "\"d\"" } })
+    );
+    assert_eq!(document.keys(&["b"]), Some(vec!["c".to_string()]));
+    assert_eq!(document.keys(&["a"]), None);
+  }
+
+  #[test]
+  fn set_replaces_in_place() {
+    let text = "{\n  // target\n  \"target\": \"es2017\", // old\n  \"strict\": true,\n}\n";
+    let result = edited(text, |d| d.set(&["target"], &json!("es2022")));
+    assert_eq!(
+      result,
+      "{\n  // target\n  \"target\": \"es2022\", // old\n  \"strict\": true,\n}\n"
+    );
+  }
+
+  #[test]
+  fn set_appends_after_trailing_comment() {
+    let text = "{\n  \"a\": 1 // a\n}\n";
+    let result = edited(text, |d| d.set(&["b"], &json!(2)));
+    assert_eq!(result, "{\n  \"a\": 1, // a\n  \"b\": 2\n}\n");
+  }
+
+  #[test]
+  fn set_keeps_trailing_commas() {
+    let text = "{\n  \"a\": 1,\n}\n";
+    let result = edited(text, |d| d.set(&["b"], &json!(2)));
+    assert_eq!(result, "{\n  \"a\": 1,\n  \"b\": 2,\n}\n");
+  }
+
+  #[test]
+  fn set_creates_parents() {
+    let text = "{\n  \"a\": 1\n}\n";
+    let result = edited(text, |d| {
+      d.set(&["compilerOptions", "strict"], &json!(true))
+    });
+    assert_eq!(
+      result,
+      "{\n  \"a\": 1,\n  \"compilerOptions\": {\n    \"strict\": true\n  }\n}\n"
+    );
+  }
+
+  #[test]
+  fn set_into_empty_object() {
+    assert_eq!(
+      edited("{}\n", |d| d.set(&["a"], &json!(1))),
+      "{\n  \"a\": 1\n}\n"
+    );
+    let text = "{\n  \"o\": {}\n}\n";
+    let result = edited(text, |d| d.set(&["o", "a"], &json!(1)));
+    assert_eq!(result, "{\n  \"o\": {\n    \"a\": 1\n  }\n}\n");
+  }
+
+  #[test]
+  fn set_into_single_line_object() {
+    let result = edited("{ \"a\": 1 }", |d| d.set(&["b"], &json!([1, 2])));
+    assert_eq!(result, "{ \"a\": 1, \"b\": [1,2] }");
+    let result = edited("{ \"a\": 1, }", |d| d.set(&["b"], &json!(2)));
+    assert_eq!(result, "{ \"a\": 1, \"b\": 2, }");
+  }
+
+  #[test]
+  fn set_keeps_crlf_line_endings() {
+    let text = "{\r\n  \"a\": 1 // a\r\n}\r\n";
+    let result = edited(text, |d| {
+      d.set(&["compilerOptions", "lib"], &json!(["es2022"]))
+    });
+    assert_eq!(
+      result,
+      "{\r\n  \"a\": 1, // a\r\n  \"compilerOptions\": {\r\n    \"lib\": [\r\n      \"es2022\"\r\n    ]\r\n  }\r\n}\r\n"
+    );
+  }
+
+  #[test]
+  fn set_indents_with_tabs() {
+    let text = "{\n\t\"compilerOptions\": {}\n}\n";
+    let result = edited(text, |d| {
+      d.set(&["compilerOptions", "paths"], &json!({ "@/*": ["src/*"] }))
+    });
+    assert_eq!(
+      result,
+      "{\n\t\"compilerOptions\": {\n\t\t\"paths\": {\n\t\t\t\"@/*\": [\n\t\t\t\t\"src/*\"\n\t\t\t]\n\t\t}\n\t}\n}\n"
+    );
+  }
+
+  #[test]
+  fn set_uses_the_document_indent_width() {
+    let text = "{\n    \"a\": { \"b\": 1 }\n}\n";
+    let result = edited(text, |d| d.set(&["a"], &json!({ "b": 1, "c": 2 })));
+    assert_eq!(
+      result,
+      "{\n    \"a\": {\n        \"b\": 1,\n        \"c\": 2\n    }\n}\n"
+    );
+  }
+
+  #[test]
+  fn set_through_a_scalar_fails() {
+    let mut document = Document::parse("{ \"a\": 1 }").unwrap();
+    let error = document.set(&["a", "b"], &json!(1)).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::Json);
+  }
+
+  #[test]
+  fn remove_takes_line_and_comma() {
+    let text = "{\n  // keep\n  \"a\": 1,\n  \"b\": 2, // b\n  \"c\": 3\n}\n";
+    let result = edited(text, |d| d.remove(&["b"]).map(drop));
+    assert_eq!(result, "{\n  // keep\n  \"a\": 1,\n  \"c\": 3\n}\n");
+  }
+
+  #[test]
+  fn remove_last_member_drops_previous_comma() {
+    let text = "{\n  \"a\": 1, // a\n  \"b\": 2\n}\n";
+    let result = edited(text, |d| d.remove(&["b"]).map(drop));
+    assert_eq!(result, "{\n  \"a\": 1 // a\n}\n");
+  }
+
+  #[test]
+  fn remove_missing_is_a_no_op() {
+    let mut document = Document::parse("{ \"a\": 1 }").unwrap();
+    assert!(!document.remove(&["b"]).unwrap());
+    assert!(!document.remove(&["a", "b"]).unwrap());
+    assert_eq!(document.to_string(), "{ \"a\": 1 }");
+  }
+
+  #[test]
+  fn update_touches_only_changes() {
+    let text = "{\n  // options\n  \"compilerOptions\": {\n    \"strict\": true, // on\n    \"target\": \"es2017\",\n    \"old\": 1,\n  },\n}\n";
+    let result = edited(text, |d| {
+      d.update(&json!({
+        "compilerOptions": { "strict": true, "target": "es2022", "lib": ["dom"] }
+      }))
+    });
+    assert_eq!(
+      result,
+      "{\n  // options\n  \"compilerOptions\": {\n    \"strict\": true, // on\n    \"target\": \"es2022\",\n    \"lib\": [\n      \"dom\"\n    ],\n  },\n}\n"
+    );
+  }
+
+  #[test]
+  fn rejects_truncated_input() {
+    assert!(parse_error("").contains("line 1"));
+    assert!(parse_error("{ \"a\": 1").contains("line 1"));
+    assert!(parse_error("{\n  \"a\": [1, 2").contains("line 2"));
+    assert!(parse_error("{ \"a").starts_with("unterminated string"));
+    assert!(parse_error("\"\\").starts_with("unterminated string"));
+    assert!(parse_error("{ \"a\\").starts_with("unterminated string"));
+    assert!(parse_error("\"é\\").starts_with("unterminated string"));
+    assert!(parse_error("{ /* a").contains("line 1"));
+  }
+
+  #[test]
+  fn rejects_invalid_input() {
+    assert!(parse_error("{ \"a\": tru }").starts_with("expected a value"));
+    assert!(parse_error("{ \"a\": 1- }").starts_with("invalid number"));
+    assert!(parse_error("{ \"a\": \"\\x\" }").starts_with("invalid string"));
+    assert!(parse_error("{ a: 1 }").contains("line 1 column 3"));
+    assert_eq!(
+      parse_error("{}\n}"),
+      "unexpected content after the end of the document at line 2 column 1"
+    );
+  }
+}
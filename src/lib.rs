@@ -3,118 +3,1116 @@
 #[macro_use]
 extern crate napi_derive;
 
-use clap::Command;
+mod batch;
+mod browserslist;
+mod bundler;
+mod check_lib;
+mod diff;
+mod error;
+mod exports;
+mod framework;
+mod graph;
+mod jsonc;
+mod merge;
+mod module_format;
+mod monorepo;
+mod node;
+mod package_json;
+mod presets;
+mod runtime;
+mod target;
+#[cfg(test)]
+mod testing;
+mod workspace;
+mod write;
+
+use bundler::{Bundler, BUNDLERS};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use dialoguer::{Confirm, Input, Select};
+use error::{Error, ErrorCode, Result};
+use framework::{Framework, FRAMEWORKS};
+use merge::ConflictResolution;
+use module_format::{ModuleFormat, MODULE_FORMATS};
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, JsError, Task};
+use presets::{Preset, PRESETS};
+use runtime::{Runtime, RUNTIMES};
 use serde_json::json;
-use std::fs;
-use std::process;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use target::{Target, TARGETS};
+use write::Written;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct ProjectOptions {
   project_name: String,
   strictness: Strictness,
+  target: Target,
+  /// Whether `target` was given or picked at the prompt rather than left
+  /// to its default.
+  target_chosen: bool,
+  /// The oldest Node.js major version the project runs on, if known.
+  node_major: Option<u32>,
   is_transpiler: bool,
+  module_format: ModuleFormat,
   is_library: bool,
   is_monorepo: bool,
-  is_dom: bool,
+  runtime: Runtime,
+  framework: Framework,
+  /// What builds the code when tsc does not emit it.
+  bundler: Option<Bundler>,
+  /// Set when tsc emits only the declarations of a library whose
+  /// JavaScript something else emits.
+  declaration_only: Option<DeclarationOnly>,
+  preset: Option<&'static Preset>,
+}
+
+#[derive(Debug, Clone)]
+struct DeclarationOnly {
+  /// Requires the explicit types that let tools other than tsc emit
+  /// declarations one file at a time.
+  isolated_declarations: bool,
+  declaration_dir: String,
 }
 
+#[derive(Debug, Default, Clone)]
+struct CliArgs {
+  project_name: Option<String>,
+  preset: Option<&'static Preset>,
+  strictness: Option<Strictness>,
+  target: Option<Target>,
+  is_transpiler: Option<bool>,
+  module_format: Option<ModuleFormat>,
+  is_library: Option<bool>,
+  is_monorepo: Option<bool>,
+  runtime: Option<Runtime>,
+  framework: Option<Framework>,
+  bundler: Option<Bundler>,
+  declaration_only: Option<bool>,
+  isolated_declarations: Option<bool>,
+  declaration_dir: Option<String>,
+  /// Take the plain defaults rather than ones detected from the project
+  /// directory and the running Node.js.
+  skip_detection: bool,
+  yes: bool,
+  force: bool,
+  merge: bool,
+  on_conflict: Option<ConflictResolution>,
+  dry_run: bool,
+  stdout: bool,
+}
+
+#[napi(string_enum = "lowercase")]
 #[derive(Debug)]
-enum Strictness {
+pub enum Strictness {
   Off,
   On,
   Strict,
 }
 
+/// Options accepted by `generateTsconfig`, mirroring the interactive
+/// questions. Anything left out takes the same default as `--yes`.
+#[napi(object)]
+#[derive(Default)]
+pub struct TsconfigOptions {
+  /// One of the built-in presets, e.g. `node-lib`. The other options
+  /// override its answers.
+  pub preset: Option<String>,
+  pub strictness: Option<Strictness>,
+  /// Sets both `target` and `lib`. Defaults to `es2022`, or with `detect`
+  /// to the newest version the project's Node.js supports.
+  pub target: Option<Target>,
+  pub is_transpiler: Option<bool>,
+  /// What tsc emits when `isTranspiler` is set. A `dual` build also needs
+  /// the `tsconfig.esm.json` and `tsconfig.cjs.json` the CLI writes next to
+  /// this config.
+  pub module_format: Option<ModuleFormat>,
+  pub is_library: Option<bool>,
+  pub is_monorepo: Option<bool>,
+  pub runtime: Option<Runtime>,
+  /// Sets up JSX for the framework. With `detect` set, defaults to the one
+  /// the `package.json` in the current directory depends on.
+  pub framework: Option<Framework>,
+  /// Sets up module resolution and types for the bundler when
+  /// `isTranspiler` is `false`.
+  pub bundler: Option<Bundler>,
+  /// For a library with `isTranspiler` set to `false`, whether tsc still
+  /// emits its declarations. Defaults to `true`.
+  pub declaration_only: Option<bool>,
+  /// Turns on `isolatedDeclarations` along with `declarationOnly`.
+  pub isolated_declarations: Option<bool>,
+  /// Where `declarationOnly` writes the declarations. Defaults to `dist`.
+  pub declaration_dir: Option<String>,
+  /// Deprecated, use `runtime`: `true` means `browser` and `false` `node`.
+  pub is_dom: Option<bool>,
+  /// Whether options left out default to what the CLI detects: the Node.js
+  /// version, runtime, framework, bundler and browserslist of the project in
+  /// the current directory. Defaults to `false`, so the result only depends
+  /// on the options given.
+  pub detect: Option<bool>,
+}
+
+/// Runs the initializer with `args` as the command line arguments, excluding
+/// the program name (e.g. `process.argv.slice(2)` from `bin.js`).
+///
+/// Failures are thrown as an `Error` whose `code` is one of
+/// `ERR_INVALID_ARGS`, `ERR_PROMPT`, `ERR_IO`, `ERR_JSON`, `ERR_EXISTS`,
+/// `ERR_REFERENCES` or `ERR_LIBRARY`.
 #[napi]
-pub fn run() {
-  let _cmd = Command::new("tsconfig-init")
-    .about("Initialize a TypeScript project")
-    .get_matches();
+pub fn run(env: Env, args: Option<Vec<String>>) -> napi::Result<(), ErrorCode> {
+  record_node_version(&env);
+  Ok(run_cli(args.unwrap_or_default())?)
+}
+
+/// Same as `run`, but prompts and writes files on the libuv thread pool so
+/// the event loop is not blocked. The returned promise rejects with the same
+/// coded errors `run` throws.
+#[napi(js_name = "runAsync", ts_return_type = "Promise<void>")]
+pub fn run_async(env: Env, args: Option<Vec<String>>) -> AsyncTask<RunTask> {
+  record_node_version(&env);
+  AsyncTask::new(RunTask {
+    args: args.unwrap_or_default(),
+  })
+}
+
+pub struct RunTask {
+  args: Vec<String>,
+}
+
+impl Task for RunTask {
+  type Output = Result<()>;
+  type JsValue = ();
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    Ok(run_cli(std::mem::take(&mut self.args)))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<()> {
+    // `Task` errors only carry a `Status`, so build the JS error here to keep
+    // our error code on the rejection
+    output.map_err(|e| {
+      let error = napi::Error::<ErrorCode>::from(e);
+      JsError::from(error).into_unknown(env).into()
+    })
+  }
+}
+
+/// Lets detection fall back to the Node.js running us without spawning
+/// `node --version`, which may be another version or missing entirely.
+fn record_node_version(env: &Env) {
+  if let Ok(version) = env.get_node_version() {
+    node::set_running_major(version.major);
+  }
+}
+
+fn run_cli(args: Vec<String>) -> Result<()> {
+  let argv = std::iter::once("tsconfig-init".to_string()).chain(args);
+  let matches = match cli().try_get_matches_from(argv) {
+    Ok(matches) => matches,
+    // `--help` and `--version` are reported as errors that go to stdout
+    Err(e) if !e.use_stderr() => {
+      let _ = e.print();
+      return Ok(());
+    }
+    Err(e) => return Err(e.into()),
+  };
 
-  match init() {
-    Ok(_) => (),
-    Err(e) => {
-      eprintln!("Error: {}", e);
-      process::exit(1);
+  match matches.subcommand() {
+    Some(("presets", _)) => {
+      for preset in PRESETS {
+        println!("{:<18}{}", preset.name, preset.description);
+      }
+      Ok(())
+    }
+    Some(("monorepo", matches)) => {
+      let packages = matches
+        .get_many::<String>("packages")
+        .map(|packages| packages.cloned().collect());
+      monorepo::scaffold(parse_args(matches), packages)
+    }
+    Some(("workspace", matches)) => batch::init_workspace(parse_args(matches)),
+    Some(("graph", matches)) => {
+      let config = std::env::current_dir()?.join(matches.get_one::<String>("config").unwrap());
+      let format = match matches.get_one::<String>("format").unwrap().as_str() {
+        "dot" => graph::Format::Dot,
+        "mermaid" => graph::Format::Mermaid,
+        _ => unreachable!(),
+      };
+      let graph = graph::Graph::load(&[config])?;
+      print!("{}", graph.export(format));
+      graph.check()
     }
+    Some(("check-lib", matches)) => {
+      let current_dir = std::env::current_dir()?;
+      let configs = match matches.get_many::<String>("configs") {
+        Some(configs) => configs.map(|config| current_dir.join(config)).collect(),
+        None => check_lib::default_configs(&current_dir),
+      };
+      check_lib::run(&configs)
+    }
+    _ => init(&parse_args(&matches)),
   }
 }
 
-fn init() -> Result<(), Box<dyn std::error::Error>> {
-  let options = prompt_options()?;
+/// Returns the tsconfig the CLI would write for `options`, without prompting
+/// or writing any files. Options left out take the `--yes` defaults, or with
+/// `detect` the ones the CLI would detect in the current directory.
+#[napi(
+  js_name = "generateTsconfig",
+  ts_return_type = "{ compilerOptions: Record<string, unknown> }"
+)]
+pub fn generate_tsconfig_js(
+  env: Env,
+  options: Option<TsconfigOptions>,
+) -> napi::Result<serde_json::Value, ErrorCode> {
+  record_node_version(&env);
+  Ok(generate_tsconfig(&project_options(options)?))
+}
 
-  let project_dir = if options.project_name == "." {
-    std::env::current_dir()?
-  } else {
-    let current_dir = std::env::current_dir()?;
-    current_dir.join(&options.project_name)
+/// How `mergeTsconfig` settles compiler options that the existing config
+/// already sets to a different value.
+#[napi(object)]
+#[derive(Default)]
+pub struct MergeOptions {
+  /// Per compiler option overrides, e.g. `{ target: 'existing' }`.
+  pub resolutions: Option<HashMap<String, ConflictResolution>>,
+  /// Used for conflicts not listed in `resolutions`. Defaults to `existing`.
+  pub on_conflict: Option<ConflictResolution>,
+}
+
+#[napi(object)]
+pub struct TsconfigConflict {
+  pub key: String,
+  #[napi(ts_type = "unknown")]
+  pub existing: serde_json::Value,
+  #[napi(ts_type = "unknown")]
+  pub generated: serde_json::Value,
+  pub resolution: ConflictResolution,
+}
+
+#[napi(object)]
+pub struct MergeResult {
+  #[napi(ts_type = "Record<string, unknown>")]
+  pub tsconfig: serde_json::Value,
+  pub conflicts: Vec<TsconfigConflict>,
+}
+
+/// Merges the tsconfig generated for `options` into `existing`, keeping keys
+/// like `include`, `paths` and `references`, and reports every compiler
+/// option the two disagree on along with how it was resolved.
+#[napi(js_name = "mergeTsconfig")]
+pub fn merge_tsconfig_js(
+  env: Env,
+  #[napi(ts_arg_type = "Record<string, unknown>")] existing: serde_json::Value,
+  options: Option<TsconfigOptions>,
+  merge: Option<MergeOptions>,
+) -> napi::Result<MergeResult, ErrorCode> {
+  record_node_version(&env);
+  let generated = generate_tsconfig(&project_options(options)?);
+  let merge = merge.unwrap_or_default();
+  let resolutions = merge.resolutions.unwrap_or_default();
+  let default = merge.on_conflict.unwrap_or(ConflictResolution::Existing);
+
+  let conflicts = merge::conflicts(&existing, &generated)
+    .into_iter()
+    .map(|conflict| TsconfigConflict {
+      resolution: resolutions.get(&conflict.key).copied().unwrap_or(default),
+      key: conflict.key,
+      existing: conflict.existing,
+      generated: conflict.generated,
+    })
+    .collect();
+  let tsconfig = merge::merge_tsconfig(existing, &generated, &resolutions, default);
+
+  Ok(MergeResult {
+    tsconfig,
+    conflicts,
+  })
+}
+
+/// Resolves JS options the same way the CLI resolves `--yes`.
+fn project_options(options: Option<TsconfigOptions>) -> Result<ProjectOptions> {
+  let options = options.unwrap_or_default();
+  let preset = match options.preset.as_deref() {
+    Some(name) => Some(
+      presets::find(name)
+        .ok_or_else(|| Error::new(ErrorCode::InvalidArgs, format!("unknown preset `{}`", name)))?,
+    ),
+    None => None,
+  };
+  let args = CliArgs {
+    preset,
+    strictness: options.strictness,
+    target: options.target,
+    is_transpiler: options.is_transpiler,
+    module_format: options.module_format,
+    is_library: options.is_library,
+    is_monorepo: options.is_monorepo,
+    runtime: options.runtime.or(options.is_dom.map(dom_runtime)),
+    framework: options.framework,
+    bundler: options.bundler,
+    declaration_only: options.declaration_only,
+    isolated_declarations: options.isolated_declarations,
+    declaration_dir: options.declaration_dir,
+    skip_detection: !options.detect.unwrap_or(false),
+    yes: true,
+    ..Default::default()
   };
 
-  fs::create_dir_all(&project_dir)?;
+  prompt_options(&args)
+}
 
-  let tsconfig = generate_tsconfig(&options);
-  let tsconfig_path = project_dir.join("tsconfig.json");
-  fs::write(&tsconfig_path, serde_json::to_string_pretty(&tsconfig)?)?;
+fn cli() -> Command {
+  Command::new("tsconfig-init")
+    .about("Initialize a TypeScript project")
+    .args(option_args())
+    .subcommand(Command::new("presets").about("List the built-in presets"))
+    .subcommand(
+      Command::new("monorepo")
+        .about("Scaffold a monorepo root and its packages for `tsc -b`")
+        .args(option_args())
+        .arg(
+          Arg::new("packages")
+            .long("packages")
+            .value_name("DIR")
+            .num_args(1..)
+            .value_delimiter(',')
+            .help("Package directories, relative to the project"),
+        ),
+    )
+    .subcommand(
+      Command::new("workspace")
+        .about("Generate a tsconfig.json for every package of an existing workspace")
+        .args(option_args()),
+    )
+    .subcommand(
+      Command::new("graph")
+        .about("Check the project references reachable from a tsconfig and print them as a graph")
+        .arg(
+          Arg::new("config")
+            .value_name("CONFIG")
+            .default_value("tsconfig.json")
+            .help("The tsconfig to start from"),
+        )
+        .arg(
+          Arg::new("format")
+            .long("format")
+            .value_name("FORMAT")
+            .value_parser(["dot", "mermaid"])
+            .default_value("dot")
+            .help("Graph syntax to print"),
+        ),
+    )
+    .subcommand(
+      Command::new("check-lib")
+        .about("Check that a library's tsconfig and package.json publish what they point to")
+        .arg(Arg::new("configs").value_name("CONFIG").num_args(1..).help(
+          "The configs that emit the library [default: tsconfig.json, or \
+               tsconfig.esm.json and tsconfig.cjs.json of a dual build]",
+        )),
+    )
+}
 
-  println!(
-    "tsconfig.json has been generated in {}",
-    project_dir.display()
+/// The questionnaire answers and output flags shared by every command that
+/// writes configs.
+fn option_args() -> Vec<Arg> {
+  let mut args = vec![
+    Arg::new("name")
+      .long("name")
+      .short('n')
+      .value_name("NAME")
+      .help("Project directory to create, or `.` for the current directory"),
+    Arg::new("preset")
+      .long("preset")
+      .short('p')
+      .value_name("PRESET")
+      .value_parser(PRESETS.iter().map(|preset| preset.name).collect::<Vec<_>>())
+      .help("Answer every question from a built-in preset (see `presets`)"),
+    Arg::new("strictness")
+      .long("strictness")
+      .short('s')
+      .value_name("LEVEL")
+      .value_parser(["off", "on", "strict"])
+      .help("How strict the typescript compiler should be"),
+    Arg::new("target")
+      .long("target")
+      .short('t')
+      .value_name("TARGET")
+      .value_parser(
+        TARGETS
+          .iter()
+          .map(|target| target.as_str())
+          .collect::<Vec<_>>(),
+      )
+      .help("ECMAScript version to compile to, also used for `lib`"),
+  ];
+  args.extend(bool_args(
+    "transpiler",
+    "no-transpiler",
+    "Transpile using tsc",
+    "Do not emit with tsc (use a bundler instead)",
+  ));
+  args.push(
+    Arg::new("module-format")
+      .long("module-format")
+      .value_name("FORMAT")
+      .value_parser(
+        MODULE_FORMATS
+          .iter()
+          .map(|format| format.as_str())
+          .collect::<Vec<_>>(),
+      )
+      .help("Module system tsc emits: ES modules, CommonJS or both"),
+  );
+  args.extend(bool_args(
+    "library",
+    "no-library",
+    "Build a library",
+    "Build an application",
+  ));
+  args.extend(bool_args(
+    "monorepo",
+    "no-monorepo",
+    "Build a library inside a monorepo",
+    "Not part of a monorepo",
+  ));
+  args.push(
+    Arg::new("runtime")
+      .long("runtime")
+      .short('r')
+      .value_name("RUNTIME")
+      .value_parser(
+        RUNTIMES
+          .iter()
+          .map(|runtime| runtime.as_str())
+          .collect::<Vec<_>>(),
+      )
+      .help("Environment the code runs in"),
+  );
+  args.push(
+    Arg::new("framework")
+      .long("framework")
+      .value_name("FRAMEWORK")
+      .value_parser(
+        FRAMEWORKS
+          .iter()
+          .map(|framework| framework.as_str())
+          .collect::<Vec<_>>(),
+      )
+      .help("UI framework to set up JSX for"),
   );
+  args.push(
+    Arg::new("bundler")
+      .long("bundler")
+      .value_name("BUNDLER")
+      .value_parser(
+        BUNDLERS
+          .iter()
+          .map(|bundler| bundler.as_str())
+          .collect::<Vec<_>>(),
+      )
+      .help("Bundler that builds the code when tsc does not emit it"),
+  );
+  args.extend(bool_args(
+    "declaration-only",
+    "no-declaration-only",
+    "Have tsc emit only the declarations of a library the bundler builds",
+    "Have tsc emit nothing for a library the bundler builds",
+  ));
+  args.extend(bool_args(
+    "isolated-declarations",
+    "no-isolated-declarations",
+    "Require the explicit types that let other tools emit declarations",
+    "Let tsc infer the types in declarations",
+  ));
+  args.push(
+    Arg::new("declaration-dir")
+      .long("declaration-dir")
+      .value_name("DIR")
+      .help("Where tsc writes the declarations of a library the bundler builds [default: dist]"),
+  );
+  // Kept from before `--runtime`, as `--runtime browser` and `--runtime node`
+  args.extend(
+    bool_args(
+      "dom",
+      "no-dom",
+      "Target a dom (browser) environment",
+      "Target a non-dom environment",
+    )
+    .map(|arg| arg.hide(true).conflicts_with("runtime")),
+  );
+  args.extend([
+    Arg::new("yes")
+      .long("yes")
+      .short('y')
+      .action(ArgAction::SetTrue)
+      .help("Accept the defaults for every option not given on the command line"),
+    Arg::new("force")
+      .long("force")
+      .short('f')
+      .action(ArgAction::SetTrue)
+      .help("Replace an existing tsconfig.json (a .bak copy is kept)"),
+    Arg::new("merge")
+      .long("merge")
+      .short('m')
+      .action(ArgAction::SetTrue)
+      .conflicts_with("force")
+      .help("Merge into an existing tsconfig.json (a .bak copy is kept)"),
+    Arg::new("on-conflict")
+      .long("on-conflict")
+      .value_name("SIDE")
+      .value_parser(["existing", "generated"])
+      .help("Which value to keep when merging changes an existing compiler option"),
+    Arg::new("dry-run")
+      .long("dry-run")
+      .action(ArgAction::SetTrue)
+      .help("Show the config and where it would be written, without writing anything"),
+    Arg::new("stdout")
+      .long("stdout")
+      .action(ArgAction::SetTrue)
+      .conflicts_with("dry-run")
+      .help("Print only the generated JSON instead of writing it"),
+  ]);
+  args
+}
+
+/// A `--foo` / `--no-foo` pair where the last one given wins.
+fn bool_args(
+  name: &'static str,
+  negated: &'static str,
+  help: &'static str,
+  negated_help: &'static str,
+) -> [Arg; 2] {
+  [
+    Arg::new(name)
+      .long(name)
+      .action(ArgAction::SetTrue)
+      .overrides_with(negated)
+      .help(help),
+    Arg::new(negated)
+      .long(negated)
+      .action(ArgAction::SetTrue)
+      .overrides_with(name)
+      .help(negated_help),
+  ]
+}
+
+fn bool_arg(matches: &ArgMatches, name: &str, negated: &str) -> Option<bool> {
+  if matches.get_flag(name) {
+    Some(true)
+  } else if matches.get_flag(negated) {
+    Some(false)
+  } else {
+    None
+  }
+}
+
+/// The runtime meant by the old yes/no DOM question.
+fn dom_runtime(is_dom: bool) -> Runtime {
+  if is_dom {
+    Runtime::Browser
+  } else {
+    Runtime::Node
+  }
+}
+
+fn parse_args(matches: &ArgMatches) -> CliArgs {
+  let strictness = matches
+    .get_one::<String>("strictness")
+    .map(|level| match level.as_str() {
+      "off" => Strictness::Off,
+      "on" => Strictness::On,
+      "strict" => Strictness::Strict,
+      _ => unreachable!(),
+    });
+
+  CliArgs {
+    project_name: matches.get_one::<String>("name").cloned(),
+    preset: matches
+      .get_one::<String>("preset")
+      .and_then(|name| presets::find(name)),
+    strictness,
+    target: matches
+      .get_one::<String>("target")
+      .and_then(|name| Target::from_name(name)),
+    is_transpiler: bool_arg(matches, "transpiler", "no-transpiler"),
+    module_format: matches
+      .get_one::<String>("module-format")
+      .and_then(|name| ModuleFormat::from_name(name)),
+    is_library: bool_arg(matches, "library", "no-library"),
+    is_monorepo: bool_arg(matches, "monorepo", "no-monorepo"),
+    runtime: matches
+      .get_one::<String>("runtime")
+      .and_then(|name| Runtime::from_name(name))
+      .or(bool_arg(matches, "dom", "no-dom").map(dom_runtime)),
+    framework: matches
+      .get_one::<String>("framework")
+      .and_then(|name| Framework::from_name(name)),
+    bundler: matches
+      .get_one::<String>("bundler")
+      .and_then(|name| Bundler::from_name(name)),
+    declaration_only: bool_arg(matches, "declaration-only", "no-declaration-only"),
+    isolated_declarations: bool_arg(matches, "isolated-declarations", "no-isolated-declarations"),
+    declaration_dir: matches.get_one::<String>("declaration-dir").cloned(),
+    skip_detection: false,
+    yes: matches.get_flag("yes"),
+    force: matches.get_flag("force"),
+    merge: matches.get_flag("merge"),
+    on_conflict: matches
+      .get_one::<String>("on-conflict")
+      .map(|side| match side.as_str() {
+        "existing" => ConflictResolution::Existing,
+        "generated" => ConflictResolution::Generated,
+        _ => unreachable!(),
+      }),
+    dry_run: matches.get_flag("dry-run"),
+    stdout: matches.get_flag("stdout"),
+  }
+}
+
+fn init(args: &CliArgs) -> Result<()> {
+  let options = prompt_options(args)?;
+  warn(&options);
+
+  let project_dir = project_dir(&options.project_name)?;
+
+  let mut tsconfig = generate_tsconfig(&options);
+  let config_file = options.runtime.config_file();
+  let tsconfig_path = project_dir.join(config_file);
+
+  // Reference the workspace packages this one depends on
+  if options.is_monorepo && options.runtime != Runtime::Deno {
+    if let Some(workspace) = workspace::Workspace::enclosing(&project_dir)? {
+      if let Some(package) = workspace.package_at(&project_dir) {
+        let references = workspace.references(package);
+        if !references.is_empty() {
+          tsconfig["references"] = monorepo::reference_list(&references);
+        }
+      }
+    }
+  }
+
+  let has_references = tsconfig.get("references").is_some();
+  let files = config_files(&options, &project_dir, tsconfig);
+  let mut written = Written::Skipped;
+  // The configs as written, which a merge may have kept settings of
+  let mut written_files = Vec::new();
+  for (path, config) in &files {
+    if args.stdout && files.len() > 1 {
+      println!("// {}", path.display());
+    }
+    let (file_written, written_config) = write::write_config(args, path, config)?;
+    if path == &tsconfig_path {
+      written = file_written;
+    }
+    written_files.push((path.clone(), written_config));
+  }
+
+  // Deno projects need no package.json, and --stdout prints only the configs
+  let mut package_json_written = Written::Skipped;
+  if written != Written::Skipped && options.runtime != Runtime::Deno && !args.stdout {
+    let fields = package_json::generate(&options, &project_dir, &written_files);
+    package_json_written = package_json::write(args, &project_dir.join("package.json"), &fields)?;
+  }
+
+  if written != Written::Skipped && !args.dry_run && !args.stdout {
+    let mut names: Vec<String> = files
+      .iter()
+      .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+      .collect();
+    match package_json_written {
+      Written::Created => names.push("package.json".to_string()),
+      Written::Merged => names.push("package.json fields".to_string()),
+      Written::Replaced | Written::Skipped => {}
+    }
+    println!(
+      "{} {} been generated in {}",
+      names.join(", "),
+      if names.len() == 1 { "has" } else { "have" },
+      project_dir.display()
+    );
+    if has_references {
+      graph::warn(&[tsconfig_path]);
+    }
+  }
   Ok(())
 }
 
-fn prompt_options() -> Result<ProjectOptions, Box<dyn std::error::Error>> {
-  let project_name = Input::<String>::new()
-    .with_prompt("What is the name of your project?")
-    .default(".".into())
-    .interact()?;
+/// Every config file to write for `options` into `dir`: the main one holding
+/// `tsconfig`, then those of a dual module build.
+fn config_files(
+  options: &ProjectOptions,
+  dir: &Path,
+  tsconfig: serde_json::Value,
+) -> Vec<(PathBuf, serde_json::Value)> {
+  let config_file = options.runtime.config_file();
+  let mut files = vec![(dir.join(config_file), tsconfig)];
+  if options.is_transpiler && options.module_format == ModuleFormat::Dual {
+    for (name, config) in module_format::dual_configs(config_file, "dist") {
+      files.push((dir.join(name), config));
+    }
+  }
+  files
+}
 
-  let strictness_options = &[
-    "Relaxed (Few checks)",
-    "Balanced (Recommended)",
-    "Rigorous (Maximum safety)",
-  ];
-  let strictness_idx = Select::new()
-    .with_prompt("How strict should the typescript compiler be?")
-    .default(1)
-    .items(strictness_options)
-    .interact()?;
-
-  let strictness = match strictness_idx {
-    0 => Strictness::Off,
-    1 => Strictness::On,
-    2 => Strictness::Strict,
-    _ => unreachable!(),
+fn project_dir(project_name: &str) -> Result<PathBuf> {
+  let current_dir = std::env::current_dir()?;
+  if project_name == "." {
+    Ok(current_dir)
+  } else {
+    Ok(current_dir.join(project_name))
+  }
+}
+
+/// Fills in `ProjectOptions` from the command line, only prompting for the
+/// values that were not supplied by a flag or preset (or taking their
+/// defaults with `--yes`).
+fn prompt_options(args: &CliArgs) -> Result<ProjectOptions> {
+  let preset = match args.preset {
+    Some(preset) => Some(preset),
+    None if args.yes => None,
+    None => {
+      let mut choices = vec!["Custom (answer each question)".to_string()];
+      choices.extend(
+        PRESETS
+          .iter()
+          .map(|preset| format!("{} ({})", preset.name, preset.description)),
+      );
+      let choice_idx = Select::new()
+        .with_prompt("Start from a preset?")
+        .default(0)
+        .items(&choices)
+        .interact()?;
+
+      choice_idx.checked_sub(1).map(|idx| &PRESETS[idx])
+    }
+  };
+  // Questions the preset has no answer for take their defaults, as with
+  // `--yes`
+  let use_defaults = args.yes || preset.is_some();
+
+  let project_name = match &args.project_name {
+    Some(name) => name.clone(),
+    None if args.yes => ".".into(),
+    None => Input::<String>::new()
+      .with_prompt("What is the name of your project?")
+      .default(".".into())
+      .interact()?,
+  };
+
+  let strictness = match args.strictness.or(preset.map(|p| p.strictness)) {
+    Some(strictness) => strictness,
+    None if args.yes => Strictness::On,
+    None => {
+      let strictness_options = &[
+        "Relaxed (Few checks)",
+        "Balanced (Recommended)",
+        "Rigorous (Maximum safety)",
+      ];
+      let strictness_idx = Select::new()
+        .with_prompt("How strict should the typescript compiler be?")
+        .default(1)
+        .items(strictness_options)
+        .interact()?;
+
+      match strictness_idx {
+        0 => Strictness::Off,
+        1 => Strictness::On,
+        2 => Strictness::Strict,
+        _ => unreachable!(),
+      }
+    }
   };
 
-  let is_transpiler = Confirm::new()
-    .with_prompt("Are you transpiling using tsc?")
-    .default(true)
-    .interact()?;
+  let is_transpiler = confirm(
+    args.is_transpiler.or(preset.map(|p| p.is_transpiler)),
+    args.yes,
+    "Are you transpiling using tsc?",
+    true,
+  )?;
+
+  let is_library = confirm(
+    args.is_library.or(preset.map(|p| p.is_library)),
+    args.yes,
+    "Are you building a library?",
+    false,
+  )?;
 
-  let is_library = Confirm::new()
-    .with_prompt("Are you building a library?")
-    .default(false)
-    .interact()?;
+  let is_monorepo = confirm(
+    args.is_monorepo.or(preset.map(|p| p.is_monorepo)),
+    args.yes,
+    "Are you building for a library in a monorepo?",
+    false,
+  )?;
+
+  let dir = if args.skip_detection {
+    None
+  } else {
+    Some(project_dir(&project_name)?)
+  };
+  let dir = dir.as_deref();
+  let runtime = match args.runtime.or(preset.map(|p| p.runtime)) {
+    Some(runtime) => runtime,
+    None => {
+      let default = dir.and_then(runtime::detect).unwrap_or(Runtime::Node);
+      if args.yes {
+        default
+      } else {
+        let choices: Vec<&str> = RUNTIMES.iter().map(|r| r.description()).collect();
+        let runtime_idx = Select::new()
+          .with_prompt("Where will the code run?")
+          .default(RUNTIMES.iter().position(|r| *r == default).unwrap())
+          .items(&choices)
+          .interact()?;
 
-  let is_monorepo = Confirm::new()
-    .with_prompt("Are you building for a library in a monorepo?")
-    .default(false)
-    .interact()?;
+        RUNTIMES[runtime_idx]
+      }
+    }
+  };
+
+  let framework = match args.framework {
+    Some(framework) => framework,
+    None => {
+      let default = dir.and_then(framework::detect).unwrap_or(Framework::None);
+      if use_defaults {
+        default
+      } else {
+        let choices: Vec<&str> = FRAMEWORKS.iter().map(|f| f.description()).collect();
+        let framework_idx = Select::new()
+          .with_prompt("Which UI framework do you use?")
+          .default(FRAMEWORKS.iter().position(|f| *f == default).unwrap())
+          .items(&choices)
+          .interact()?;
+
+        FRAMEWORKS[framework_idx]
+      }
+    }
+  };
+
+  let node = dir.and_then(node::detect);
+  let target = match args.target {
+    Some(target) => target,
+    None => {
+      let detected = detect_target(dir, runtime, node);
+      let default = detected.as_ref().map_or(target::DEFAULT_TARGET, |d| d.0);
+      if use_defaults {
+        default
+      } else {
+        let choices: Vec<String> = TARGETS
+          .iter()
+          .map(|target| match &detected {
+            Some((detected, reason)) if detected == target => {
+              format!("{} ({})", target.as_str(), reason)
+            }
+            _ => target.as_str().to_string(),
+          })
+          .collect();
+        let target_idx = Select::new()
+          .with_prompt("Which ECMAScript version should the code compile to?")
+          .default(TARGETS.iter().position(|t| *t == default).unwrap())
+          .items(&choices)
+          .interact()?;
+
+        TARGETS[target_idx]
+      }
+    }
+  };
+
+  let emits_modules = is_transpiler
+    && matches!(
+      runtime,
+      Runtime::Node | Runtime::Browser | Runtime::WebWorker
+    );
+  let module_format = match args.module_format {
+    Some(format) => format,
+    None if !emits_modules => ModuleFormat::Esm,
+    None => {
+      let default = match node {
+        Some(node) if !node::support(node.major).esm => ModuleFormat::Cjs,
+        _ => ModuleFormat::Esm,
+      };
+      if use_defaults {
+        default
+      } else {
+        let choices: Vec<&str> = MODULE_FORMATS.iter().map(|f| f.description()).collect();
+        let format_idx = Select::new()
+          .with_prompt("Which module format should tsc emit?")
+          .default(MODULE_FORMATS.iter().position(|f| *f == default).unwrap())
+          .items(&choices)
+          .interact()?;
+
+        MODULE_FORMATS[format_idx]
+      }
+    }
+  };
+
+  let bundler = match args.bundler {
+    Some(bundler) => Some(bundler),
+    None if is_transpiler || runtime == Runtime::Deno => None,
+    None => {
+      let default = dir.and_then(|dir| bundler::detect(dir, runtime));
+      if use_defaults {
+        default
+      } else {
+        let mut choices: Vec<&str> = BUNDLERS.iter().map(|b| b.description()).collect();
+        choices.push("Another or none");
+        let default_idx = default
+          .and_then(|default| BUNDLERS.iter().position(|b| *b == default))
+          .unwrap_or(BUNDLERS.len());
+        let bundler_idx = Select::new()
+          .with_prompt("Which bundler builds the code?")
+          .default(default_idx)
+          .items(&choices)
+          .interact()?;
+
+        BUNDLERS.get(bundler_idx).copied()
+      }
+    }
+  };
 
-  let is_dom = Confirm::new()
-    .with_prompt("Is your project for a dom (browser) environment?")
-    .default(false)
-    .interact()?;
+  let declaration_only = if is_library && !is_transpiler && runtime != Runtime::Deno {
+    declaration_only(args, preset)?
+  } else {
+    None
+  };
 
   Ok(ProjectOptions {
     project_name,
     strictness,
+    target,
+    target_chosen: args.target.is_some() || !use_defaults,
+    node_major: node.map(|node| node.major),
     is_transpiler,
+    module_format,
     is_library,
     is_monorepo,
-    is_dom,
+    runtime,
+    framework,
+    bundler,
+    declaration_only,
+    preset,
+  })
+}
+
+/// Asks whether tsc should still emit the declarations of a library it
+/// does not build, and how.
+fn declaration_only(args: &CliArgs, preset: Option<&Preset>) -> Result<Option<DeclarationOnly>> {
+  let declaration_only = confirm(
+    args.declaration_only.or(preset.map(|p| p.declaration_only)),
+    args.yes,
+    "Should tsc emit the declaration files while the bundler emits the JavaScript?",
+    true,
+  )?;
+  if !declaration_only {
+    return Ok(None);
+  }
+
+  let isolated_declarations = confirm(
+    args.isolated_declarations,
+    args.yes || preset.is_some(),
+    "Use isolatedDeclarations, so other tools can emit the declarations too?",
+    false,
+  )?;
+  Ok(Some(DeclarationOnly {
+    isolated_declarations,
+    declaration_dir: args
+      .declaration_dir
+      .clone()
+      .unwrap_or_else(|| "dist".to_string()),
+  }))
+}
+
+/// The target to suggest for the project in `dir` and why: the lowest its
+/// browserslist allows for browser projects, the newest syntax for runtimes
+/// that keep up with it, otherwise the newest its Node.js version supports.
+/// Without a `dir` only the runtime is taken into account.
+fn detect_target(
+  dir: Option<&Path>,
+  runtime: Runtime,
+  node: Option<node::Detected>,
+) -> Option<(Target, String)> {
+  match runtime {
+    Runtime::Browser | Runtime::WebWorker => {
+      let dir = dir?;
+      // browserslist itself applies `defaults` when there is no config
+      let Some(browsers) = browserslist::find(dir) else {
+        let resolved = browserslist::resolve(&["defaults".to_string()]);
+        return Some((
+          resolved.target,
+          "lowest browserslist defaults allow".to_string(),
+        ));
+      };
+      let resolved = browserslist::resolve(&browsers.queries);
+      for warning in &resolved.warnings {
+        eprintln!("{}: {}", browsers.path.display(), warning);
+      }
+      return Some((
+        resolved.target,
+        format!("lowest {} allows", browsers.path.display()),
+      ));
+    }
+    Runtime::Bun | Runtime::Deno | Runtime::Edge => {
+      return Some((
+        Target::EsNext,
+        format!("{} keeps up with new syntax", runtime.description()),
+      ))
+    }
+    Runtime::Node => {}
+  }
+  node.map(|node| {
+    (
+      node::support(node.major).target,
+      format!(
+        "newest Node.js {} supports, from {}",
+        node.major, node.source
+      ),
+    )
+  })
+}
+
+fn confirm(value: Option<bool>, yes: bool, prompt: &str, default: bool) -> Result<bool> {
+  match value {
+    Some(value) => Ok(value),
+    None if yes => Ok(default),
+    None => Ok(
+      Confirm::new()
+        .with_prompt(prompt)
+        .default(default)
+        .interact()?,
+    ),
+  }
+}
+
+/// Sets every option in `extra`, adding to the `types` already listed rather
+/// than replacing them.
+fn extend_compiler_options(compiler_options: &mut serde_json::Value, extra: serde_json::Value) {
+  for (key, value) in extra.as_object().unwrap() {
+    match compiler_options.get_mut(key) {
+      Some(serde_json::Value::Array(types)) if key == "types" => {
+        for value in value.as_array().unwrap() {
+          if !types.contains(value) {
+            types.push(value.clone());
+          }
+        }
+      }
+      _ => compiler_options[key] = value.clone(),
+    }
+  }
+}
+
+/// Prints the combinations of answers that will not work well together.
+fn warn(options: &ProjectOptions) {
+  for warning in bundler::warnings(options) {
+    eprintln!("warning: {}", warning);
+  }
+}
+
+/// The compiler options a published library needs, so that tsc emits its
+/// declarations and `src/index.ts` always lands at `<outDir>/index.js`.
+fn library_options() -> serde_json::Value {
+  json!({
+      "declaration": true,
+      "rootDir": "src",
   })
 }
 
@@ -122,7 +1120,7 @@ fn generate_tsconfig(options: &ProjectOptions) -> serde_json::Value {
   let mut compiler_options = json!({
       "esModuleInterop": true,
       "skipLibCheck": true,
-      "target": "es2022",
+      "target": options.target.as_str(),
       "allowJs": true,
       "resolveJsonModule": true,
       "moduleDetection": "force",
@@ -155,9 +1153,14 @@ fn generate_tsconfig(options: &ProjectOptions) -> serde_json::Value {
 
   // Transpiling settings
   if options.is_transpiler {
+    let module = match (options.runtime, options.node_major) {
+      (Runtime::Node, Some(major)) => node::support(major).module,
+      (Runtime::Browser | Runtime::WebWorker, _) => "ESNext",
+      _ => "NodeNext",
+    };
     compiler_options.as_object_mut().unwrap().extend(
       json!({
-          "module": "NodeNext",
+          "module": module,
           "outDir": "dist",
           "sourceMap": true,
       })
@@ -165,6 +1168,43 @@ fn generate_tsconfig(options: &ProjectOptions) -> serde_json::Value {
       .unwrap()
       .clone(),
     );
+    // Browsers load what a bundler or import map resolves, not node_modules
+    // the way Node.js does
+    if matches!(options.runtime, Runtime::Browser | Runtime::WebWorker) {
+      compiler_options["moduleResolution"] = json!("bundler");
+    }
+    match options.module_format {
+      ModuleFormat::Esm => {}
+      ModuleFormat::Cjs => {
+        compiler_options.as_object_mut().unwrap().extend(
+          module_format::commonjs_options()
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+      }
+      // tsconfig.esm.json and tsconfig.cjs.json emit, this one only checks
+      ModuleFormat::Dual => {
+        let compiler_options = compiler_options.as_object_mut().unwrap();
+        compiler_options.remove("outDir");
+        compiler_options.insert("noEmit".to_string(), json!(true));
+      }
+    }
+  } else if let Some(declaration_only) = &options.declaration_only {
+    // The bundler emits the JavaScript, tsc only the declarations
+    compiler_options.as_object_mut().unwrap().extend(
+      json!({
+          "module": "preserve",
+          "emitDeclarationOnly": true,
+          "declarationDir": declaration_only.declaration_dir,
+      })
+      .as_object()
+      .unwrap()
+      .clone(),
+    );
+    if declaration_only.isolated_declarations {
+      compiler_options["isolatedDeclarations"] = json!(true);
+    }
   } else {
     compiler_options.as_object_mut().unwrap().extend(
       json!({
@@ -177,12 +1217,12 @@ fn generate_tsconfig(options: &ProjectOptions) -> serde_json::Value {
     );
   }
 
-  // Library settings
-  if options.is_library {
+  // Library settings, which tsc cannot honour under noEmit
+  if options.is_library && (options.is_transpiler || options.declaration_only.is_some()) {
     compiler_options
       .as_object_mut()
       .unwrap()
-      .insert("declaration".to_string(), json!(true));
+      .extend(library_options().as_object().unwrap().clone());
   }
 
   // Monorepo settings
@@ -198,20 +1238,72 @@ fn generate_tsconfig(options: &ProjectOptions) -> serde_json::Value {
     );
   }
 
-  // DOM settings
-  if options.is_dom {
-    compiler_options
-      .as_object_mut()
-      .unwrap()
-      .insert("lib".to_string(), json!(["es2022", "dom", "dom.iterable"]));
-  } else {
-    compiler_options
-      .as_object_mut()
-      .unwrap()
-      .insert("lib".to_string(), json!(["es2022"]));
+  // Runtime settings
+  let mut lib = vec![options.target.as_str()];
+  lib.extend(options.runtime.libs());
+  compiler_options
+    .as_object_mut()
+    .unwrap()
+    .insert("lib".to_string(), json!(lib));
+  match options.runtime {
+    Runtime::Bun => {
+      compiler_options.as_object_mut().unwrap().extend(
+        json!({
+            "module": "preserve",
+            "moduleResolution": "bundler",
+            "types": ["bun"],
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+      );
+    }
+    // Workers are always bundled before they are deployed
+    Runtime::Edge => {
+      compiler_options.as_object_mut().unwrap().extend(
+        json!({
+            "module": "preserve",
+            "moduleResolution": "bundler",
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+      );
+    }
+    Runtime::Deno => {
+      compiler_options
+        .as_object_mut()
+        .unwrap()
+        .insert("lib".to_string(), json!(["deno.window"]));
+    }
+    // Otherwise every installed @types package is loaded
+    Runtime::Node => extend_compiler_options(&mut compiler_options, json!({ "types": ["node"] })),
+    Runtime::Browser | Runtime::WebWorker => {}
   }
 
-  json!({
+  // Framework settings
+  extend_compiler_options(&mut compiler_options, options.framework.compiler_options());
+
+  // Bundler settings
+  if let Some(bundler) = options.bundler.filter(|_| !options.is_transpiler) {
+    extend_compiler_options(&mut compiler_options, bundler.compiler_options());
+  }
+
+  // Preset settings
+  if let Some(preset) = options.preset {
+    extend_compiler_options(&mut compiler_options, (preset.compiler_options)());
+  }
+
+  let mut tsconfig = json!({
       "compilerOptions": compiler_options
-  })
+  });
+  if options.runtime == Runtime::Deno {
+    return runtime::deno_config(tsconfig);
+  }
+  // Otherwise allowJs picks up files outside of rootDir, such as config
+  // files, and tsc fails with TS6059
+  if let Some(root_dir) = tsconfig["compilerOptions"].get("rootDir").cloned() {
+    tsconfig["include"] = json!([root_dir]);
+  }
+  tsconfig
 }
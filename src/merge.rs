@@ -0,0 +1,172 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Which side wins when a compiler option differs between the existing
+/// tsconfig and the generated one.
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+  Existing,
+  Generated,
+}
+
+/// A compiler option that the existing tsconfig sets to a different value
+/// than the one we generated.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+  pub key: String,
+  pub existing: Value,
+  pub generated: Value,
+}
+
+/// Lists the generated compiler options that would change a value already
+/// set in `existing`.
+pub fn conflicts(existing: &Value, generated: &Value) -> Vec<Conflict> {
+  let existing_options = compiler_options(existing);
+  let generated_options = compiler_options(generated);
+
+  generated_options
+    .iter()
+    .filter_map(|(key, generated)| {
+      let existing = existing_options.get(key)?;
+      (existing != generated).then(|| Conflict {
+        key: key.clone(),
+        existing: existing.clone(),
+        generated: generated.clone(),
+      })
+    })
+    .collect()
+}
+
+/// Overlays the generated `compilerOptions` onto an existing config, keeping
+/// every other key (`include`, `paths`, ...) as it was and only adding the
+/// generated top-level keys it lacks, plus any missing `references`.
+/// Conflicting options are settled by `resolutions`, falling back to
+/// `default` for keys it does not mention. A generated `lib` follows the
+/// `target` that ends up in the config, so keeping an existing `target`
+/// does not pull in the newer library the generated one would have.
+pub fn merge_tsconfig(
+  mut existing: Value,
+  generated: &Value,
+  resolutions: &HashMap<String, ConflictResolution>,
+  default: ConflictResolution,
+) -> Value {
+  let Some(existing_object) = existing.as_object_mut() else {
+    return generated.clone();
+  };
+  let Some(generated_object) = generated.as_object() else {
+    return existing;
+  };
+
+  for (key, value) in generated_object {
+    match (key.as_str(), existing_object.get_mut(key)) {
+      ("compilerOptions", _) => {}
+      // Projects the existing config does not reference yet are appended
+      ("references", Some(Value::Array(references))) => {
+        for reference in value.as_array().into_iter().flatten() {
+          if !references.iter().any(|r| r["path"] == reference["path"]) {
+            references.push(reference.clone());
+          }
+        }
+      }
+      (_, Some(_)) => {}
+      (_, None) => {
+        existing_object.insert(key.clone(), value.clone());
+      }
+    }
+  }
+
+  let generated_options = compiler_options(generated);
+  if generated_options.is_empty() {
+    return existing;
+  }
+  if !existing_object
+    .get("compilerOptions")
+    .is_some_and(Value::is_object)
+  {
+    existing_object.insert("compilerOptions".to_string(), Map::new().into());
+  }
+  let merged = existing_object["compilerOptions"].as_object_mut().unwrap();
+
+  for (key, value) in &generated_options {
+    let keep_existing = merged.contains_key(key)
+      && *resolutions.get(key).unwrap_or(&default) == ConflictResolution::Existing;
+    if !keep_existing {
+      merged.insert(key.clone(), value.clone());
+    }
+  }
+
+  if let (Some(Value::String(kept)), Some(Value::String(generated_target))) = (
+    merged.get("target").cloned(),
+    generated_options.get("target"),
+  ) {
+    if merged.get("lib") == generated_options.get("lib")
+      && !kept.eq_ignore_ascii_case(generated_target)
+    {
+      if let Some(Value::Array(lib)) = merged.get_mut("lib") {
+        for entry in lib.iter_mut() {
+          if entry
+            .as_str()
+            .is_some_and(|entry| entry.eq_ignore_ascii_case(generated_target))
+          {
+            *entry = kept.to_lowercase().into();
+          }
+        }
+      }
+    }
+  }
+  existing
+}
+
+fn compiler_options(tsconfig: &Value) -> Map<String, Value> {
+  tsconfig["compilerOptions"]
+    .as_object()
+    .cloned()
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn merge(existing: Value, generated: Value) -> Value {
+    merge_tsconfig(
+      existing,
+      &generated,
+      &HashMap::new(),
+      ConflictResolution::Existing,
+    )
+  }
+
+  #[test]
+  fn lib_follows_a_kept_target() {
+    let merged = merge(
+      json!({ "compilerOptions": { "target": "ES5" } }),
+      json!({ "compilerOptions": { "target": "es2023", "lib": ["es2023", "dom"] } }),
+    );
+    assert_eq!(merged["compilerOptions"]["target"], "ES5");
+    assert_eq!(merged["compilerOptions"]["lib"], json!(["es5", "dom"]));
+  }
+
+  #[test]
+  fn existing_lib_is_left_alone() {
+    let merged = merge(
+      json!({ "compilerOptions": { "target": "es2017", "lib": ["es2019"] } }),
+      json!({ "compilerOptions": { "target": "es2023", "lib": ["es2023"] } }),
+    );
+    assert_eq!(merged["compilerOptions"]["lib"], json!(["es2019"]));
+  }
+
+  #[test]
+  fn generated_target_keeps_generated_lib() {
+    let merged = merge_tsconfig(
+      json!({ "compilerOptions": { "target": "es5" } }),
+      &json!({ "compilerOptions": { "target": "es2023", "lib": ["es2023"] } }),
+      &HashMap::new(),
+      ConflictResolution::Generated,
+    );
+    assert_eq!(merged["compilerOptions"]["target"], "es2023");
+    assert_eq!(merged["compilerOptions"]["lib"], json!(["es2023"]));
+  }
+}
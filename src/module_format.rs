@@ -0,0 +1,81 @@
+//! Which module system tsc emits: ES modules, CommonJS, or both from one
+//! source tree.
+
+use serde_json::{json, Value};
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ModuleFormat {
+  Esm,
+  Cjs,
+  /// ES modules and CommonJS, built by `tsconfig.esm.json` and
+  /// `tsconfig.cjs.json` on top of a shared `tsconfig.json`.
+  Dual,
+}
+
+pub const MODULE_FORMATS: &[ModuleFormat] =
+  &[ModuleFormat::Esm, ModuleFormat::Cjs, ModuleFormat::Dual];
+
+impl ModuleFormat {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ModuleFormat::Esm => "esm",
+      ModuleFormat::Cjs => "cjs",
+      ModuleFormat::Dual => "dual",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<ModuleFormat> {
+    MODULE_FORMATS
+      .iter()
+      .copied()
+      .find(|format| format.as_str() == name)
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      ModuleFormat::Esm => "ES modules",
+      ModuleFormat::Cjs => "CommonJS",
+      ModuleFormat::Dual => "Both (dual package)",
+    }
+  }
+}
+
+/// Compiler options that make tsc emit CommonJS.
+pub fn commonjs_options() -> Value {
+  json!({
+      "module": "CommonJS",
+      "moduleResolution": "Node10",
+      // `import` statements are an error in CommonJS files under
+      // verbatimModuleSyntax
+      "verbatimModuleSyntax": false,
+  })
+}
+
+/// The per-format configs of a dual build, which extend `base` (the shared
+/// `tsconfig.json`) and each emit into their own directory under `out_dir`.
+pub fn dual_configs(base: &str, out_dir: &str) -> Vec<(&'static str, Value)> {
+  let mut cjs_options = commonjs_options();
+  cjs_options["noEmit"] = json!(false);
+  cjs_options["outDir"] = json!(format!("{}/cjs", out_dir));
+
+  vec![
+    (
+      "tsconfig.esm.json",
+      json!({
+          "extends": format!("./{}", base),
+          "compilerOptions": {
+              "noEmit": false,
+              "outDir": format!("{}/esm", out_dir),
+          },
+      }),
+    ),
+    (
+      "tsconfig.cjs.json",
+      json!({
+          "extends": format!("./{}", base),
+          "compilerOptions": cjs_options,
+      }),
+    ),
+  ]
+}
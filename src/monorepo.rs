@@ -0,0 +1,175 @@
+//! Scaffolds a monorepo that `tsc -b` can build: a shared
+//! `tsconfig.base.json`, a solution-style root `tsconfig.json` referencing
+//! every package, and a `tsconfig.json` per package extending the base.
+
+use crate::error::{Error, ErrorCode, Result};
+use crate::graph;
+use crate::module_format::ModuleFormat;
+use crate::runtime::Runtime;
+use crate::workspace::Workspace;
+use crate::write::{self, Written};
+use crate::{generate_tsconfig, project_dir, prompt_options, warn, CliArgs, ProjectOptions};
+use dialoguer::Input;
+use serde_json::{json, Value};
+use std::path::Path;
+
+pub fn scaffold(mut args: CliArgs, packages: Option<Vec<String>>) -> Result<()> {
+  args.is_monorepo = Some(true);
+  let options = prompt_options(&args)?;
+  warn(&options);
+  if options.runtime == Runtime::Deno {
+    return Err(Error::new(
+      ErrorCode::InvalidArgs,
+      "Deno does not build with `tsc -b`, use a Deno workspace instead",
+    ));
+  }
+  if options.module_format == ModuleFormat::Dual {
+    return Err(Error::new(
+      ErrorCode::InvalidArgs,
+      "dual module builds are not supported by `tsc -b` project references, pick esm or cjs",
+    ));
+  }
+  // Referenced projects are composite, which tsc refuses along with noEmit
+  if !options.is_transpiler && options.declaration_only.is_none() {
+    return Err(Error::new(
+      ErrorCode::InvalidArgs,
+      "`tsc -b` has to emit the packages it builds, pass --transpiler, or --library with --declaration-only for packages a bundler builds",
+    ));
+  }
+  let root = project_dir(&options.project_name)?;
+
+  let discovered = match packages {
+    Some(_) => None,
+    None => Workspace::discover(&root)?.filter(|w| !w.packages.is_empty()),
+  };
+  let workspace = match (discovered, packages) {
+    (Some(workspace), _) => {
+      eprintln!(
+        "Found {} workspace packages in {}",
+        workspace.packages.len(),
+        root.display()
+      );
+      workspace
+    }
+    (None, packages) => {
+      let packages = match packages {
+        Some(packages) => packages,
+        None if args.yes => {
+          return Err(Error::new(
+            ErrorCode::InvalidArgs,
+            "no workspace packages found, pass the package directories with --packages",
+          ))
+        }
+        None => Input::<String>::new()
+          .with_prompt("Which directories hold your packages? (comma separated)")
+          .interact()?
+          .split(',')
+          .map(str::to_string)
+          .collect(),
+      };
+      let packages: Vec<String> = packages
+        .iter()
+        .map(|package| normalize(package))
+        .filter(|package| !package.is_empty())
+        .collect();
+      Workspace::load(&root, &packages)?
+    }
+  };
+  let packages: Vec<String> = workspace.packages.iter().map(|p| p.dir.clone()).collect();
+
+  let mut files = vec![
+    (root.join("tsconfig.base.json"), base_tsconfig(&options)),
+    (root.join("tsconfig.json"), solution_tsconfig(&packages)),
+  ];
+  for package in &workspace.packages {
+    files.push((
+      root.join(&package.dir).join("tsconfig.json"),
+      package_tsconfig(&options, &package.dir, &workspace.references(package)),
+    ));
+  }
+
+  let mut written = 0;
+  for (path, config) in &files {
+    if args.stdout {
+      println!("// {}", path.display());
+    }
+    if write::write_config(&args, path, config)?.0 != Written::Skipped {
+      written += 1;
+    }
+  }
+
+  if !args.dry_run && !args.stdout {
+    println!(
+      "Wrote {} configs for {} packages in {}, build them with `tsc -b`",
+      written,
+      packages.len(),
+      root.display()
+    );
+    graph::warn(&[root.join("tsconfig.json")]);
+  }
+  Ok(())
+}
+
+/// The compiler options every package shares.
+pub fn base_tsconfig(options: &ProjectOptions) -> Value {
+  let mut base = generate_tsconfig(options);
+  let compiler_options = base["compilerOptions"].as_object_mut().unwrap();
+  // Relative paths resolve against the config that sets them, so these go
+  // in each package instead
+  for key in ["outDir", "rootDir", "declarationDir", "tsBuildInfoFile"] {
+    compiler_options.remove(key);
+  }
+  base.as_object_mut().unwrap().remove("include");
+  base
+}
+
+/// A root config that builds nothing itself and only references packages.
+pub fn solution_tsconfig(packages: &[String]) -> Value {
+  let paths: Vec<String> = packages
+    .iter()
+    .map(|package| format!("./{}", package))
+    .collect();
+
+  json!({
+      "files": [],
+      "references": reference_list(&paths),
+  })
+}
+
+/// The config of a single package, `package` being its directory relative to
+/// the monorepo root and `references` the packages it depends on.
+pub fn package_tsconfig(options: &ProjectOptions, package: &str, references: &[String]) -> Value {
+  let depth = Path::new(package).components().count();
+  let mut compiler_options = json!({ "rootDir": "src" });
+  if options.is_transpiler {
+    compiler_options["outDir"] = json!("dist");
+  }
+  if let Some(declaration_only) = &options.declaration_only {
+    compiler_options["declarationDir"] = json!(declaration_only.declaration_dir);
+  }
+
+  let mut tsconfig = json!({
+      "extends": format!("{}tsconfig.base.json", "../".repeat(depth)),
+      "compilerOptions": compiler_options,
+      "include": ["src"],
+  });
+  if !references.is_empty() {
+    tsconfig["references"] = reference_list(references);
+  }
+  tsconfig
+}
+
+/// `references` entries for the given relative project paths.
+pub fn reference_list(paths: &[String]) -> Value {
+  paths.iter().map(|path| json!({ "path": path })).collect()
+}
+
+/// `./packages/a/` -> `packages/a`, with forward slashes.
+fn normalize(package: &str) -> String {
+  package
+    .trim()
+    .replace('\\', "/")
+    .trim_start_matches("./")
+    .trim_end_matches('/')
+    .to_string()
+}
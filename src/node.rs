@@ -0,0 +1,261 @@
+//! Works out which Node.js version a project runs on, from its
+//! `package.json` `engines.node`, `.nvmrc` or `.node-version`, or failing
+//! that the Node.js running us, and what that version supports.
+
+use crate::target::Target;
+use crate::workspace;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What tsc should emit for a minimum Node.js major version.
+#[derive(Debug)]
+pub struct NodeSupport {
+  pub major: u32,
+  /// The newest ECMAScript version it fully supports.
+  pub target: Target,
+  pub module: &'static str,
+  /// Whether it loads ES modules without a flag.
+  pub esm: bool,
+}
+
+/// Ordered by `major`; a version uses the last row at or below it.
+const NODE_SUPPORT: &[NodeSupport] = &[
+  NodeSupport {
+    major: 8,
+    target: Target::Es2017,
+    module: "Node16",
+    esm: false,
+  },
+  NodeSupport {
+    major: 10,
+    target: Target::Es2018,
+    module: "Node16",
+    esm: false,
+  },
+  // ES modules are unflagged from 12.20
+  NodeSupport {
+    major: 12,
+    target: Target::Es2019,
+    module: "Node16",
+    esm: true,
+  },
+  NodeSupport {
+    major: 14,
+    target: Target::Es2020,
+    module: "Node16",
+    esm: true,
+  },
+  NodeSupport {
+    major: 16,
+    target: Target::Es2021,
+    module: "Node16",
+    esm: true,
+  },
+  NodeSupport {
+    major: 18,
+    target: Target::Es2022,
+    module: "Node16",
+    esm: true,
+  },
+  // `require()` of ES modules, which `NodeNext` models, from 20.19
+  NodeSupport {
+    major: 20,
+    target: Target::Es2023,
+    module: "NodeNext",
+    esm: true,
+  },
+  NodeSupport {
+    major: 22,
+    target: Target::Es2024,
+    module: "NodeNext",
+    esm: true,
+  },
+];
+
+/// LTS codenames as used by `lts/<name>` in `.nvmrc`.
+const LTS_CODENAMES: &[(&str, u32)] = &[
+  ("argon", 4),
+  ("boron", 6),
+  ("carbon", 8),
+  ("dubnium", 10),
+  ("erbium", 12),
+  ("fermium", 14),
+  ("gallium", 16),
+  ("hydrogen", 18),
+  ("iron", 20),
+  ("jod", 22),
+  ("krypton", 24),
+];
+
+pub fn support(major: u32) -> &'static NodeSupport {
+  NODE_SUPPORT
+    .iter()
+    .rev()
+    .find(|support| support.major <= major)
+    .unwrap_or(&NODE_SUPPORT[0])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+  Engines,
+  Nvmrc,
+  NodeVersion,
+  Running,
+}
+
+impl fmt::Display for Source {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Source::Engines => "package.json engines",
+      Source::Nvmrc => ".nvmrc",
+      Source::NodeVersion => ".node-version",
+      Source::Running => "running Node.js",
+    })
+  }
+}
+
+/// The lowest Node.js major version the project in `dir` has to run on, and
+/// where it came from.
+#[derive(Debug, Clone, Copy)]
+pub struct Detected {
+  pub major: u32,
+  pub source: Source,
+}
+
+/// Looks for a pinned version in `dir`, falling back to the Node.js running
+/// us.
+pub fn detect(dir: &Path) -> Option<Detected> {
+  pinned(dir).or_else(|| {
+    RUNNING_MAJOR.get().map(|major| Detected {
+      major: *major,
+      source: Source::Running,
+    })
+  })
+}
+
+/// The major version of the Node.js that loaded the addon, as reported by
+/// N-API. Unset when there is none, as in Rust tests.
+static RUNNING_MAJOR: OnceLock<u32> = OnceLock::new();
+
+/// Records the major version of the Node.js running us, which stays the
+/// same for the life of the process.
+pub fn set_running_major(major: u32) {
+  let _ = RUNNING_MAJOR.set(major);
+}
+
+/// The version pinned by the project files in `dir`, if any. `engines`
+/// comes first since it states the oldest version the code must support.
+pub fn pinned(dir: &Path) -> Option<Detected> {
+  let engines =
+    workspace::manifest(dir).and_then(|manifest| min_major(manifest["engines"]["node"].as_str()?));
+  let from_file = |name| {
+    let text = fs::read_to_string(dir.join(name)).ok()?;
+    version_file_major(text.lines().next()?)
+  };
+
+  [
+    (engines, Source::Engines),
+    (from_file(".nvmrc"), Source::Nvmrc),
+    (from_file(".node-version"), Source::NodeVersion),
+  ]
+  .into_iter()
+  .find_map(|(major, source)| major.map(|major| Detected { major, source }))
+}
+
+/// The lowest major version a semver range such as `>=18`, `^16.14 || >=18`
+/// or `18.x` allows, or `None` if it has no lower bound.
+pub fn min_major(range: &str) -> Option<u32> {
+  let mut min = None;
+  for alternative in range.split("||") {
+    // `16 - 20` is bounded below by its first version
+    let lower = alternative.split(" - ").next().unwrap_or("");
+    let alternative_min = lower
+      .split_whitespace()
+      .filter(|comparator| !comparator.starts_with('<'))
+      .filter_map(|comparator| parse_major(comparator.trim_start_matches(['>', '=', '^', '~'])))
+      .max()?;
+    min = Some(min.map_or(alternative_min, |min: u32| min.min(alternative_min)));
+  }
+  min
+}
+
+/// The major version named by a line of `.nvmrc` or `.node-version`:
+/// `20`, `v20.11.1`, `lts/iron` or `lts/*`.
+pub fn version_file_major(line: &str) -> Option<u32> {
+  let line = line.trim();
+  match line.strip_prefix("lts/") {
+    Some("*") => LTS_CODENAMES.last().map(|(_, major)| *major),
+    Some(codename) => LTS_CODENAMES
+      .iter()
+      .find(|(name, _)| name.eq_ignore_ascii_case(codename))
+      .map(|(_, major)| *major),
+    None => parse_major(line),
+  }
+}
+
+/// The major version out of `v20.11.1`, `20` and the like.
+pub fn parse_major(version: &str) -> Option<u32> {
+  let version = version.trim().trim_start_matches(['v', 'V']);
+  version.split('.').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn min_major_takes_the_lowest_alternative() {
+    assert_eq!(min_major(">=18"), Some(18));
+    assert_eq!(min_major(">= 18.0.0"), Some(18));
+    assert_eq!(min_major("18.x"), Some(18));
+    assert_eq!(min_major("^16.14 || >=18"), Some(16));
+    assert_eq!(min_major(">=20 || ^18.18"), Some(18));
+    assert_eq!(min_major(">=14 <22"), Some(14));
+    assert_eq!(min_major("16 - 20"), Some(16));
+    assert_eq!(min_major("~20.11.1"), Some(20));
+  }
+
+  #[test]
+  fn min_major_without_a_lower_bound() {
+    assert_eq!(min_major("<20"), None);
+    assert_eq!(min_major(">=18 || <16"), None);
+    assert_eq!(min_major("*"), None);
+    assert_eq!(min_major(""), None);
+  }
+
+  #[test]
+  fn version_file_major_reads_lts_aliases() {
+    assert_eq!(version_file_major("20"), Some(20));
+    assert_eq!(version_file_major(" v20.11.1 "), Some(20));
+    assert_eq!(version_file_major("lts/iron"), Some(20));
+    assert_eq!(version_file_major("lts/Hydrogen"), Some(18));
+    assert_eq!(
+      version_file_major("lts/*"),
+      LTS_CODENAMES.last().map(|(_, major)| *major)
+    );
+    assert_eq!(version_file_major("lts/unknown"), None);
+    assert_eq!(version_file_major("node"), None);
+  }
+
+  #[test]
+  fn parse_major_strips_the_prefix() {
+    assert_eq!(parse_major("v20.11.1"), Some(20));
+    assert_eq!(parse_major("V18"), Some(18));
+    assert_eq!(parse_major("22.0.0-nightly"), Some(22));
+    assert_eq!(parse_major("x"), None);
+  }
+
+  #[test]
+  fn support_uses_the_row_at_or_below() {
+    assert_eq!(support(16).target, Target::Es2021);
+    assert_eq!(support(17).target, Target::Es2021);
+    assert_eq!(support(20).module, "NodeNext");
+    assert_eq!(support(30).target, Target::Es2024);
+    // Older than the table still gets its first row
+    assert_eq!(support(6).major, 8);
+    assert!(!support(10).esm);
+    assert!(support(12).esm);
+  }
+}
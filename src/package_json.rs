@@ -0,0 +1,397 @@
+//! Creates the `package.json` next to a generated config, or fills in the
+//! fields an existing one lacks, so its scripts and entry points match what
+//! the config builds.
+
+use crate::error::Result;
+use crate::exports;
+use crate::jsonc;
+use crate::module_format::ModuleFormat;
+use crate::workspace;
+use crate::write::{self, Written};
+use crate::{CliArgs, ProjectOptions};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// A field to set, by its path from the root of `package.json`.
+#[derive(Debug)]
+pub struct Field {
+  pub path: Vec<String>,
+  pub value: Value,
+  /// Whether a different value already there is replaced rather than kept.
+  pub sync: bool,
+}
+
+/// The fields `options` call for, in the order npm lays them out. `files`
+/// are the config files written into `dir`, whose `outDir`s decide where the
+/// entry points go.
+pub fn generate(options: &ProjectOptions, dir: &Path, files: &[(PathBuf, Value)]) -> Vec<Field> {
+  let mut fields = vec![field(&["name"], json!(package_name(options, dir)))];
+  let config_file = options.runtime.config_file();
+  // A merge may have kept an existing CommonJS `module`
+  let module = exports::written_options(files, dir, &dir.join(config_file))
+    .get("module")
+    .and_then(Value::as_str)
+    .map(str::to_lowercase);
+  if options.module_format != ModuleFormat::Cjs && module.as_deref() != Some("commonjs") {
+    fields.push(field(&["type"], json!("module")));
+  }
+  if options.is_library {
+    if let Some((entry, exports)) = exports::entry_points(
+      options.module_format,
+      options.bundler,
+      dir,
+      config_file,
+      files,
+    ) {
+      // npm packs the directories the entry points are in
+      let mut files: Vec<&str> = Vec::new();
+      for path in [entry.js.as_ref(), entry.types.as_ref()]
+        .into_iter()
+        .flatten()
+      {
+        let dir = path.trim_start_matches("./").split('/').next().unwrap();
+        if !files.contains(&dir) {
+          files.push(dir);
+        }
+      }
+      fields.push(field(&["files"], json!(files)));
+      if let Some(js) = &entry.js {
+        fields.push(field(&["main"], json!(js)));
+      }
+      if let Some(types) = &entry.types {
+        fields.push(field(&["types"], json!(types)));
+      }
+      // Without the JavaScript there is nothing to export yet
+      if entry.js.is_some() {
+        fields.push(Field {
+          path: vec!["exports".to_string(), ".".to_string()],
+          value: exports["."].clone(),
+          sync: true,
+        });
+      }
+    }
+  }
+  if let Some(build) = build_script(options) {
+    fields.push(field(&["scripts", "build"], json!(build)));
+  }
+  // tsc refuses `--noEmit` on top of `emitDeclarationOnly`
+  let typecheck = match options.declaration_only {
+    Some(_) => "tsc --noEmit --emitDeclarationOnly false",
+    None => "tsc --noEmit",
+  };
+  fields.push(field(&["scripts", "typecheck"], json!(typecheck)));
+
+  // tsc fails on `types` it cannot find, so install what provides them
+  let manifest = workspace::manifest(dir).unwrap_or_default();
+  let compiler_options = exports::written_options(files, dir, &dir.join(config_file));
+  for types in compiler_options
+    .get("types")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_str)
+  {
+    let (package, version) = types_package(types, options.node_major);
+    if !workspace::depends_on(&manifest, &[&package]) {
+      fields.push(field(&["devDependencies", &package], json!(version)));
+    }
+  }
+  fields
+}
+
+/// The package that provides the `types` entry `types` and the version to
+/// add it at: `@types/node` for the project's Node.js, or the latest one.
+fn types_package(types: &str, node_major: Option<u32>) -> (String, String) {
+  match types {
+    "node" => (
+      "@types/node".to_string(),
+      node_major.map_or("*".to_string(), |major| format!("^{}", major)),
+    ),
+    "bun" => ("@types/bun".to_string(), "*".to_string()),
+    // `vite/client` and the like ship with the package itself
+    _ => {
+      let segments = if types.starts_with('@') { 2 } else { 1 };
+      let package: Vec<&str> = types.split('/').take(segments).collect();
+      (package.join("/"), "*".to_string())
+    }
+  }
+}
+
+/// Creates `package.json` at `path` from `fields`, or adds the ones it does
+/// not have yet. Fields already there are left as they are unless they are
+/// synced, as is every script but the ones named in `fields`.
+pub fn write(args: &CliArgs, path: &Path, fields: &[Field]) -> Result<Written> {
+  let existing = write::read_existing(path)?;
+  let mut document = jsonc::Document::parse(existing.as_deref().unwrap_or("{}\n"))?;
+  let current = document.value();
+
+  let mut changed = false;
+  for field in fields {
+    let field_path: Vec<&str> = field.path.iter().map(String::as_str).collect();
+    // A single path or set of conditions is the "." export written short
+    let short_exports = field_path[0] == "exports" && !is_subpath_map(&current["exports"]);
+    let (owner, value) = if short_exports {
+      (vec!["exports"], current.get("exports"))
+    } else {
+      // Each script, export and dependency is its own field, anything else
+      // is owned by its top key
+      let owner = match field_path[0] {
+        "scripts" | "exports" | "devDependencies" => field_path[..2].to_vec(),
+        _ => field_path[..1].to_vec(),
+      };
+      let value = owner.iter().try_fold(&current, |value, key| value.get(key));
+      (owner, value)
+    };
+
+    match value {
+      None => {}
+      Some(_) if !field.sync => continue,
+      Some(value) if *value != field.value => eprintln!(
+        "package.json {}: using {} instead of {}",
+        exports::label(&field.path),
+        field.value,
+        value
+      ),
+      Some(_) if !is_ordered(&document, &owner, &field.value) => eprintln!(
+        "package.json {}: putting the types condition first",
+        exports::label(&field.path)
+      ),
+      Some(_) => continue,
+    }
+
+    document.remove(&owner)?;
+    set_ordered(&mut document, &mut field_path.clone(), &field.value)?;
+    changed = true;
+  }
+  if !changed {
+    return Ok(Written::Skipped);
+  }
+
+  write::write_contents(args, path, existing.as_deref(), &document.to_string())?;
+  Ok(match existing {
+    Some(_) => Written::Merged,
+    None => Written::Created,
+  })
+}
+
+fn field(path: &[&str], value: Value) -> Field {
+  Field {
+    path: path.iter().map(|key| key.to_string()).collect(),
+    value,
+    sync: false,
+  }
+}
+
+/// Whether `exports` maps subpaths (`"."`, `"./utils"`, ...) rather than
+/// being the `"."` export itself.
+fn is_subpath_map(exports: &Value) -> bool {
+  exports
+    .as_object()
+    .is_some_and(|exports| exports.keys().all(|key| key.starts_with('.')))
+}
+
+/// The order conditions go in: `types` first and `default` last, as Node.js
+/// and TypeScript take the first one that matches.
+fn condition_rank(key: &str) -> u8 {
+  match key {
+    "types" => 0,
+    "default" => 2,
+    _ => 1,
+  }
+}
+
+fn ordered_keys(object: &Map<String, Value>) -> Vec<&String> {
+  let mut keys: Vec<&String> = object.keys().collect();
+  keys.sort_by_key(|key| condition_rank(key));
+  keys
+}
+
+/// Whether the objects at `path` list their keys in the order
+/// `set_ordered` would write them.
+fn is_ordered(document: &jsonc::Document, path: &[&str], value: &Value) -> bool {
+  let Some(object) = value.as_object() else {
+    return true;
+  };
+  let keys = ordered_keys(object);
+  document.keys(path).is_some_and(|existing| {
+    existing.iter().eq(keys.iter().copied())
+      && keys
+        .iter()
+        .all(|key| is_ordered(document, &[path, &[key.as_str()]].concat(), &object[*key]))
+  })
+}
+
+/// Sets `value` at `path` one member at a time, since `serde_json` objects
+/// forget the order their keys were inserted in.
+fn set_ordered<'a>(
+  document: &mut jsonc::Document,
+  path: &mut Vec<&'a str>,
+  value: &'a Value,
+) -> Result<()> {
+  match value.as_object() {
+    Some(object) if !object.is_empty() => {
+      for key in ordered_keys(object) {
+        path.push(key);
+        set_ordered(document, path, &object[key])?;
+        path.pop();
+      }
+      Ok(())
+    }
+    _ => document.set(path, value),
+  }
+}
+
+/// The project's directory name, as npm accepts it.
+fn package_name(options: &ProjectOptions, dir: &Path) -> String {
+  let name = Path::new(&options.project_name)
+    .file_name()
+    .or(dir.file_name())
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  name
+    .to_lowercase()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+/// What `npm run build` runs: tsc when it emits, else the bundler, then tsc
+/// for the declarations if it emits those.
+fn build_script(options: &ProjectOptions) -> Option<String> {
+  if !options.is_transpiler {
+    let bundle = options
+      .bundler
+      .map(|bundler| bundler.build_command(options.module_format));
+    return match (bundle, &options.declaration_only) {
+      (Some(bundle), Some(_)) => Some(format!("{} && tsc", bundle)),
+      (None, Some(_)) => Some("tsc".to_string()),
+      (bundle, None) => bundle.map(str::to_string),
+    };
+  }
+  Some(match options.module_format {
+    // Under `"type": "module"` the CommonJS output needs its own marker,
+    // written with node since cmd.exe's echo keeps the quotes
+    ModuleFormat::Dual => "tsc -p tsconfig.esm.json && tsc -p tsconfig.cjs.json \
+       && node -e \"require('fs').writeFileSync('dist/cjs/package.json', JSON.stringify({ type: 'commonjs' }))\""
+      .to_string(),
+    _ if options.is_monorepo => "tsc -b".to_string(),
+    _ => "tsc".to_string(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::tree;
+  use std::fs;
+
+  fn exports_field(value: Value) -> Field {
+    Field {
+      path: vec!["exports".to_string(), ".".to_string()],
+      value,
+      sync: true,
+    }
+  }
+
+  /// Writes `fields` into a `package.json` holding `existing`, returning the
+  /// document it ends up with.
+  fn written(name: &str, existing: &str, fields: &[Field]) -> jsonc::Document {
+    let dir = tree(
+      &format!("package-json-{}", name),
+      &[("package.json", existing)],
+    );
+    let path = dir.join("package.json");
+    let args = CliArgs {
+      yes: true,
+      ..Default::default()
+    };
+    write(&args, &path, fields).unwrap();
+    jsonc::Document::parse(&fs::read_to_string(path).unwrap()).unwrap()
+  }
+
+  fn conditions() -> Value {
+    json!({ "types": "./dist/index.d.ts", "default": "./dist/index.js" })
+  }
+
+  #[test]
+  fn replaces_a_string_export() {
+    let document = written(
+      "string",
+      r#"{ "exports": "./index.js" }"#,
+      &[exports_field(conditions())],
+    );
+    assert_eq!(document.value()["exports"], json!({ ".": conditions() }));
+    assert_eq!(
+      document.keys(&["exports", "."]),
+      Some(vec!["types".to_string(), "default".to_string()])
+    );
+  }
+
+  #[test]
+  fn puts_the_types_condition_first() {
+    let existing = r#"{
+  "exports": {
+    ".": { "default": "./dist/index.js", "types": "./dist/index.d.ts" },
+    "./utils": "./dist/utils.js"
+  }
+}"#;
+    let document = written("order", existing, &[exports_field(conditions())]);
+    assert_eq!(
+      document.keys(&["exports", "."]),
+      Some(vec!["types".to_string(), "default".to_string()])
+    );
+    assert_eq!(document.value()["exports"]["./utils"], "./dist/utils.js");
+  }
+
+  #[test]
+  fn writes_the_dual_map_in_order() {
+    let dual = json!({
+        "import": { "types": "./dist/esm/index.d.ts", "default": "./dist/esm/index.js" },
+        "require": { "types": "./dist/cjs/index.d.ts", "default": "./dist/cjs/index.js" },
+    });
+    let document = written(
+      "dual",
+      "{\n  \"name\": \"dual\"\n}\n",
+      &[exports_field(dual.clone())],
+    );
+    assert_eq!(document.value()["exports"]["."], dual);
+    for condition in ["import", "require"] {
+      assert_eq!(
+        document.keys(&["exports", ".", condition]),
+        Some(vec!["types".to_string(), "default".to_string()])
+      );
+    }
+    assert!(is_ordered(&document, &["exports", "."], &dual));
+  }
+
+  #[test]
+  fn is_ordered_checks_nested_objects() {
+    let value = json!({ "import": conditions() });
+    let ordered = jsonc::Document::parse(
+      r#"{ "import": { "types": "./dist/index.d.ts", "default": "./dist/index.js" } }"#,
+    )
+    .unwrap();
+    assert!(is_ordered(&ordered, &[], &value));
+    let unordered = jsonc::Document::parse(
+      r#"{ "import": { "default": "./dist/index.js", "types": "./dist/index.d.ts" } }"#,
+    )
+    .unwrap();
+    assert!(!is_ordered(&unordered, &[], &value));
+  }
+
+  #[test]
+  fn keeps_fields_that_are_not_synced() {
+    let document = written(
+      "keep",
+      r#"{ "main": "lib/index.js" }"#,
+      &[
+        field(&["main"], json!("./dist/index.js")),
+        field(&["scripts", "build"], json!("tsc")),
+      ],
+    );
+    assert_eq!(
+      document.value(),
+      json!({ "main": "lib/index.js", "scripts": { "build": "tsc" } })
+    );
+  }
+}
@@ -0,0 +1,81 @@
+use crate::runtime::Runtime;
+use crate::Strictness;
+use serde_json::{json, Value};
+
+/// A named answer to every question in the questionnaire, plus compiler
+/// options layered on top of what `generate_tsconfig` produces.
+#[derive(Debug)]
+pub struct Preset {
+  pub name: &'static str,
+  pub description: &'static str,
+  pub strictness: Strictness,
+  pub is_transpiler: bool,
+  pub is_library: bool,
+  pub is_monorepo: bool,
+  pub runtime: Runtime,
+  /// For a library a bundler builds, whether tsc emits its declarations.
+  pub declaration_only: bool,
+  pub compiler_options: fn() -> Value,
+}
+
+pub const PRESETS: &[Preset] = &[
+  Preset {
+    name: "node-app",
+    description: "Node.js application compiled with tsc",
+    strictness: Strictness::Strict,
+    is_transpiler: true,
+    is_library: false,
+    is_monorepo: false,
+    runtime: Runtime::Node,
+    declaration_only: false,
+    compiler_options: || json!({}),
+  },
+  Preset {
+    name: "node-lib",
+    description: "Library published to npm for Node.js, compiled with tsc",
+    strictness: Strictness::Strict,
+    is_transpiler: true,
+    is_library: true,
+    is_monorepo: false,
+    runtime: Runtime::Node,
+    declaration_only: false,
+    compiler_options: || json!({ "declarationMap": true, "rootDir": "src" }),
+  },
+  Preset {
+    name: "browser-app",
+    description: "Browser application built by a bundler",
+    strictness: Strictness::Strict,
+    is_transpiler: false,
+    is_library: false,
+    is_monorepo: false,
+    runtime: Runtime::Browser,
+    declaration_only: false,
+    compiler_options: || json!({ "useDefineForClassFields": true }),
+  },
+  Preset {
+    name: "bundler-lib",
+    description: "Library whose JavaScript is emitted by a bundler",
+    strictness: Strictness::Strict,
+    is_transpiler: false,
+    is_library: true,
+    is_monorepo: false,
+    runtime: Runtime::Node,
+    declaration_only: true,
+    compiler_options: || json!({ "moduleResolution": "bundler" }),
+  },
+  Preset {
+    name: "monorepo-package",
+    description: "Package inside a monorepo built with `tsc -b`",
+    strictness: Strictness::Strict,
+    is_transpiler: true,
+    is_library: true,
+    is_monorepo: true,
+    runtime: Runtime::Node,
+    declaration_only: false,
+    compiler_options: || json!({ "rootDir": "src" }),
+  },
+];
+
+pub fn find(name: &str) -> Option<&'static Preset> {
+  PRESETS.iter().find(|preset| preset.name == name)
+}
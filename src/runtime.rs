@@ -0,0 +1,161 @@
+//! The environment the code runs in, which decides the `lib`, `types` and
+//! module settings, and for Deno which file the config goes in.
+
+use crate::workspace;
+use serde_json::Value;
+use std::path::Path;
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Runtime {
+  Node,
+  Bun,
+  Deno,
+  /// A browser page, with the DOM.
+  Browser,
+  /// A web or service worker, without the DOM.
+  WebWorker,
+  /// Edge runtimes such as Cloudflare Workers or Vercel Edge Functions.
+  Edge,
+}
+
+pub const RUNTIMES: &[Runtime] = &[
+  Runtime::Node,
+  Runtime::Bun,
+  Runtime::Deno,
+  Runtime::Browser,
+  Runtime::WebWorker,
+  Runtime::Edge,
+];
+
+/// The compiler options `deno.json` accepts; Deno decides the rest itself.
+pub const DENO_COMPILER_OPTIONS: &[&str] = &[
+  "allowJs",
+  "allowUnreachableCode",
+  "allowUnusedLabels",
+  "checkJs",
+  "exactOptionalPropertyTypes",
+  "experimentalDecorators",
+  "isolatedDeclarations",
+  "jsx",
+  "jsxFactory",
+  "jsxFragmentFactory",
+  "jsxImportSource",
+  "jsxImportSourceTypes",
+  "lib",
+  "noErrorTruncation",
+  "noFallthroughCasesInSwitch",
+  "noImplicitAny",
+  "noImplicitOverride",
+  "noImplicitReturns",
+  "noImplicitThis",
+  "noPropertyAccessFromIndexSignature",
+  "noUncheckedIndexedAccess",
+  "noUnusedLocals",
+  "noUnusedParameters",
+  "rootDirs",
+  "strict",
+  "strictBindCallApply",
+  "strictFunctionTypes",
+  "strictNullChecks",
+  "strictPropertyInitialization",
+  "types",
+  "useUnknownInCatchVariables",
+];
+
+/// Dependencies that mean a package runs in a browser.
+const BROWSER_DEPENDENCIES: &[&str] = &[
+  "react-dom",
+  "preact",
+  "vue",
+  "svelte",
+  "solid-js",
+  "lit",
+  "@angular/core",
+  "next",
+  "nuxt",
+  "vite",
+];
+
+impl Runtime {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Runtime::Node => "node",
+      Runtime::Bun => "bun",
+      Runtime::Deno => "deno",
+      Runtime::Browser => "browser",
+      Runtime::WebWorker => "webworker",
+      Runtime::Edge => "edge",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Runtime> {
+    RUNTIMES
+      .iter()
+      .copied()
+      .find(|runtime| runtime.as_str() == name)
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      Runtime::Node => "Node.js",
+      Runtime::Bun => "Bun",
+      Runtime::Deno => "Deno",
+      Runtime::Browser => "Browser",
+      Runtime::WebWorker => "Web or service worker",
+      Runtime::Edge => "Edge runtime (Cloudflare Workers, Vercel Edge, ...)",
+    }
+  }
+
+  /// Libraries to add to the ECMAScript one in `lib`.
+  pub fn libs(&self) -> &'static [&'static str] {
+    match self {
+      Runtime::Browser => &["dom", "dom.iterable"],
+      Runtime::WebWorker | Runtime::Edge => &["webworker"],
+      Runtime::Node | Runtime::Bun | Runtime::Deno => &[],
+    }
+  }
+
+  /// The file the config is written to.
+  pub fn config_file(&self) -> &'static str {
+    match self {
+      Runtime::Deno => "deno.json",
+      _ => "tsconfig.json",
+    }
+  }
+}
+
+/// Guesses the runtime of the project in `dir` from its config files and
+/// the dependencies in its `package.json`.
+pub fn detect(dir: &Path) -> Option<Runtime> {
+  let has_file = |names: &[&str]| names.iter().any(|name| dir.join(name).is_file());
+  if has_file(&["deno.json", "deno.jsonc"]) {
+    return Some(Runtime::Deno);
+  }
+  if has_file(&["bunfig.toml", "bun.lock", "bun.lockb"]) {
+    return Some(Runtime::Bun);
+  }
+  if has_file(&["wrangler.toml", "wrangler.json", "wrangler.jsonc"]) {
+    return Some(Runtime::Edge);
+  }
+
+  let manifest = workspace::manifest(dir)?;
+  let depends_on = |names: &[&str]| workspace::depends_on(&manifest, names);
+  if depends_on(&["@types/bun", "bun-types"]) {
+    Some(Runtime::Bun)
+  } else if depends_on(&["@cloudflare/workers-types", "wrangler"]) {
+    Some(Runtime::Edge)
+  } else if manifest.get("browser").is_some() || depends_on(BROWSER_DEPENDENCIES) {
+    Some(Runtime::Browser)
+  } else {
+    None
+  }
+}
+
+/// Keeps only the compiler options `deno.json` accepts.
+pub fn deno_config(mut config: Value) -> Value {
+  if let Some(compiler_options) = config["compilerOptions"].as_object_mut() {
+    compiler_options.retain(|key, _| DENO_COMPILER_OPTIONS.contains(&key.as_str()));
+  }
+  config
+}
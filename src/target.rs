@@ -0,0 +1,55 @@
+//! The ECMAScript version the config compiles to, which also picks the
+//! matching `lib`.
+
+#[napi(string_enum = "lowercase")]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+  Es2017,
+  Es2018,
+  Es2019,
+  Es2020,
+  Es2021,
+  Es2022,
+  Es2023,
+  Es2024,
+  EsNext,
+}
+
+pub const TARGETS: &[Target] = &[
+  Target::Es2017,
+  Target::Es2018,
+  Target::Es2019,
+  Target::Es2020,
+  Target::Es2021,
+  Target::Es2022,
+  Target::Es2023,
+  Target::Es2024,
+  Target::EsNext,
+];
+
+/// Used when no Node.js version can be found.
+pub const DEFAULT_TARGET: Target = Target::Es2022;
+
+impl Target {
+  /// The value for both `target` and `lib`.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Target::Es2017 => "es2017",
+      Target::Es2018 => "es2018",
+      Target::Es2019 => "es2019",
+      Target::Es2020 => "es2020",
+      Target::Es2021 => "es2021",
+      Target::Es2022 => "es2022",
+      Target::Es2023 => "es2023",
+      Target::Es2024 => "es2024",
+      Target::EsNext => "esnext",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Target> {
+    TARGETS
+      .iter()
+      .copied()
+      .find(|target| target.as_str().eq_ignore_ascii_case(name))
+  }
+}
@@ -0,0 +1,478 @@
+//! Discovers the packages of an npm/yarn, pnpm or lerna workspace and the
+//! project references implied by their dependencies on each other.
+
+use crate::error::{Error, ErrorCode, Result};
+use crate::jsonc;
+use crate::write::read_existing;
+use glob::Pattern;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEPENDENCY_KEYS: &[&str] = &[
+  "dependencies",
+  "devDependencies",
+  "peerDependencies",
+  "optionalDependencies",
+];
+
+#[derive(Debug)]
+pub struct Package {
+  /// Directory relative to the workspace root, with forward slashes.
+  pub dir: String,
+  /// The parsed `package.json`, or `Null` if the package has none yet.
+  pub manifest: Value,
+}
+
+impl Package {
+  pub fn name(&self) -> Option<&str> {
+    self.manifest["name"].as_str()
+  }
+
+  /// Every package name this package depends on, in any dependency field.
+  pub fn dependencies(&self) -> impl Iterator<Item = &str> {
+    DEPENDENCY_KEYS
+      .iter()
+      .filter_map(|key| self.manifest[*key].as_object())
+      .flat_map(|deps| deps.keys().map(String::as_str))
+  }
+
+  /// Whether the package is published for others to import: it is not
+  /// private and declares an entry point.
+  pub fn is_library(&self) -> bool {
+    self.manifest["private"] != Value::Bool(true)
+      && ["exports", "main", "module", "types", "typings"]
+        .iter()
+        .any(|key| self.manifest.get(key).is_some())
+  }
+}
+
+#[derive(Debug)]
+pub struct Workspace {
+  pub root: PathBuf,
+  pub packages: Vec<Package>,
+}
+
+impl Workspace {
+  /// Finds the workspace packages declared in `root`, or `None` if `root`
+  /// does not declare a workspace.
+  pub fn discover(root: &Path) -> Result<Option<Workspace>> {
+    let Some(patterns) = patterns(root)? else {
+      return Ok(None);
+    };
+    let dirs = expand(root, &patterns)?;
+    Workspace::load(root, &dirs).map(Some)
+  }
+
+  /// Finds the workspace containing `dir`, looking in `dir` and its parents.
+  pub fn enclosing(dir: &Path) -> Result<Option<Workspace>> {
+    for root in dir.ancestors() {
+      if let Some(workspace) = Workspace::discover(root)? {
+        return Ok(Some(workspace));
+      }
+    }
+    Ok(None)
+  }
+
+  /// Reads the manifests of the packages in `dirs`.
+  pub fn load(root: &Path, dirs: &[String]) -> Result<Workspace> {
+    let mut packages = Vec::new();
+    for dir in dirs {
+      let manifest = match read_existing(&root.join(dir).join("package.json"))? {
+        Some(text) => jsonc::Document::parse(&text)?.value(),
+        None => Value::Null,
+      };
+      packages.push(Package {
+        dir: dir.clone(),
+        manifest,
+      });
+    }
+
+    Ok(Workspace {
+      root: root.to_path_buf(),
+      packages,
+    })
+  }
+
+  /// The package living in `dir`, which may be absolute or relative to the
+  /// workspace root.
+  pub fn package_at(&self, dir: &Path) -> Option<&Package> {
+    let relative = dir.strip_prefix(&self.root).unwrap_or(dir);
+    let relative = to_slash(relative);
+    self.packages.iter().find(|package| package.dir == relative)
+  }
+
+  pub fn package_named(&self, name: &str) -> Option<&Package> {
+    self
+      .packages
+      .iter()
+      .find(|package| package.name() == Some(name))
+  }
+
+  /// Relative paths from `package` to every workspace package it depends on,
+  /// ready to use as `references` in its tsconfig.
+  pub fn references(&self, package: &Package) -> Vec<String> {
+    let mut references: Vec<String> = package
+      .dependencies()
+      .filter_map(|name| self.package_named(name))
+      .filter(|dependency| dependency.dir != package.dir)
+      .map(|dependency| relative(&package.dir, &dependency.dir))
+      .collect();
+    references.sort();
+    references.dedup();
+    references
+  }
+}
+
+/// The `package.json` in `dir`, or `None` if there is none or it does not
+/// parse.
+pub fn manifest(dir: &Path) -> Option<Value> {
+  let text = fs::read_to_string(dir.join("package.json")).ok()?;
+  Some(jsonc::Document::parse(&text).ok()?.value())
+}
+
+/// Whether `manifest` lists any of `names` in one of its dependency fields.
+pub fn depends_on(manifest: &Value, names: &[&str]) -> bool {
+  DEPENDENCY_KEYS
+    .iter()
+    .filter_map(|key| manifest[*key].as_object())
+    .any(|deps| names.iter().any(|name| deps.contains_key(*name)))
+}
+
+/// The workspace globs declared by `package.json` `workspaces`,
+/// `pnpm-workspace.yaml` or `lerna.json` in `root`.
+pub fn patterns(root: &Path) -> Result<Option<Vec<String>>> {
+  if let Some(text) = read_existing(&root.join("pnpm-workspace.yaml"))? {
+    return Ok(Some(pnpm_packages(&text)));
+  }
+
+  if let Some(text) = read_existing(&root.join("package.json"))? {
+    let manifest = jsonc::Document::parse(&text)?.value();
+    // Either a list of globs, or yarn's `{ "packages": [...] }`
+    let workspaces = match &manifest["workspaces"] {
+      Value::Object(workspaces) => workspaces.get("packages").cloned(),
+      Value::Null => None,
+      workspaces => Some(workspaces.clone()),
+    };
+    if let Some(workspaces) = workspaces {
+      return Ok(Some(string_list(&workspaces)));
+    }
+  }
+
+  if let Some(text) = read_existing(&root.join("lerna.json"))? {
+    let lerna = jsonc::Document::parse(&text)?.value();
+    return Ok(Some(match lerna.get("packages") {
+      Some(packages) => string_list(packages),
+      // lerna's default
+      None => vec!["packages/*".to_string()],
+    }));
+  }
+
+  Ok(None)
+}
+
+/// Expands workspace globs (with `!` exclusions) into the directories under
+/// `root` that contain a `package.json`.
+pub fn expand(root: &Path, patterns: &[String]) -> Result<Vec<String>> {
+  let mut excludes = Vec::new();
+  for pattern in patterns.iter().filter_map(|p| p.strip_prefix('!')) {
+    excludes.push(Pattern::new(clean(pattern)).map_err(invalid_pattern)?);
+  }
+
+  let mut dirs = Vec::new();
+  for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+    // Only the pattern is a glob, `root` may well contain `[` or `*`
+    let full = Path::new(&Pattern::escape(&root.to_string_lossy())).join(clean(pattern));
+    let matches = glob::glob(&full.to_string_lossy()).map_err(invalid_pattern)?;
+    for path in matches.flatten() {
+      if !path.join("package.json").is_file() {
+        continue;
+      }
+      let Ok(relative) = path.strip_prefix(root) else {
+        continue;
+      };
+      let dir = to_slash(relative);
+      if dir.split('/').any(|part| part == "node_modules")
+        || excludes.iter().any(|exclude| exclude.matches(&dir))
+      {
+        continue;
+      }
+      dirs.push(dir);
+    }
+  }
+
+  dirs.sort();
+  dirs.dedup();
+  Ok(dirs)
+}
+
+/// The `packages:` list of a `pnpm-workspace.yaml`, in block (`- a`) or flow
+/// (`[a, b]`) style.
+fn pnpm_packages(yaml: &str) -> Vec<String> {
+  let mut packages = Vec::new();
+  let mut in_packages = false;
+  for line in yaml.lines() {
+    let content = line.split(" #").next().unwrap_or("").trim_end();
+    if content.trim().is_empty() || content.trim_start().starts_with('#') {
+      continue;
+    }
+
+    if let Some(value) = content.strip_prefix("packages:") {
+      let value = value.trim();
+      if let Some(flow) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        packages.extend(flow.split(',').map(unquote).filter(|p| !p.is_empty()));
+      } else {
+        in_packages = true;
+      }
+      continue;
+    }
+
+    if in_packages {
+      match content.trim_start().strip_prefix('-') {
+        Some(item) if content.starts_with([' ', '-']) => packages.push(unquote(item)),
+        _ => in_packages = false,
+      }
+    }
+  }
+  packages
+}
+
+fn unquote(value: &str) -> String {
+  value.trim().trim_matches(['\'', '"']).to_string()
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+  value
+    .as_array()
+    .into_iter()
+    .flatten()
+    .filter_map(|item| item.as_str().map(str::to_string))
+    .collect()
+}
+
+fn clean(pattern: &str) -> &str {
+  pattern.trim_start_matches("./").trim_end_matches('/')
+}
+
+fn invalid_pattern(e: impl std::fmt::Display) -> Error {
+  Error::new(
+    ErrorCode::InvalidArgs,
+    format!("invalid workspace pattern: {}", e),
+  )
+}
+
+pub fn to_slash(path: &Path) -> String {
+  path
+    .components()
+    .map(|c| c.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// The relative path from directory `from` to directory `to`, both relative
+/// to the same root and using forward slashes.
+pub fn relative(from: &str, to: &str) -> String {
+  let from: Vec<&str> = from.split('/').filter(|p| !p.is_empty()).collect();
+  let to: Vec<&str> = to.split('/').filter(|p| !p.is_empty()).collect();
+  let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+  let mut parts = vec![".."; from.len() - common];
+  parts.extend(&to[common..]);
+  if parts.is_empty() {
+    ".".to_string()
+  } else {
+    parts.join("/")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::tree;
+
+  fn dirs(root: &Path) -> Vec<String> {
+    let workspace = Workspace::discover(root).unwrap().unwrap();
+    workspace.packages.into_iter().map(|p| p.dir).collect()
+  }
+
+  #[test]
+  fn expands_globs_with_exclusions() {
+    let root = tree(
+      "workspace-globs",
+      &[
+        (
+          "package.json",
+          r#"{ "workspaces": ["./packages/*/", "apps/*", "packages/a", "!packages/internal-*"] }"#,
+        ),
+        ("packages/a/package.json", "{}"),
+        ("packages/b/package.json", "{}"),
+        ("packages/internal-x/package.json", "{}"),
+        ("packages/no-manifest/index.ts", ""),
+        ("apps/web/package.json", "{}"),
+        ("apps/web/node_modules/dep/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["apps/web", "packages/a", "packages/b"]);
+  }
+
+  #[test]
+  fn skips_node_modules() {
+    let root = tree(
+      "workspace-node-modules",
+      &[
+        ("package.json", r#"{ "workspaces": ["**"] }"#),
+        ("lib/package.json", "{}"),
+        ("node_modules/dep/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["lib"]);
+  }
+
+  #[test]
+  fn root_is_not_a_glob() {
+    let root = tree(
+      "workspace-[root]",
+      &[
+        ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+        ("packages/a/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["packages/a"]);
+  }
+
+  #[test]
+  fn reads_yarn_packages() {
+    let root = tree(
+      "workspace-yarn",
+      &[
+        (
+          "package.json",
+          r#"{ "workspaces": { "packages": ["packages/*"], "nohoist": ["**/x"] } }"#,
+        ),
+        ("packages/a/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["packages/a"]);
+  }
+
+  #[test]
+  fn parses_pnpm_block_lists() {
+    let yaml = "# workspace\npackages:\n  # apps\n  - 'apps/*'\n  - \"packages/*\" # libraries\n  - '!**/test/**'\n\ncatalog:\n  - nope\n";
+    assert_eq!(pnpm_packages(yaml), ["apps/*", "packages/*", "!**/test/**"]);
+    assert_eq!(pnpm_packages("packages:\n- a\n- b\nother: 1\n"), ["a", "b"]);
+  }
+
+  #[test]
+  fn parses_pnpm_flow_lists() {
+    assert_eq!(
+      pnpm_packages("packages: ['apps/*', \"packages/*\",]\n"),
+      ["apps/*", "packages/*"]
+    );
+    assert!(pnpm_packages("packages: []\n").is_empty());
+    assert!(pnpm_packages("catalog:\n  react: ^18\n").is_empty());
+  }
+
+  #[test]
+  fn prefers_pnpm_over_package_json() {
+    let root = tree(
+      "workspace-pnpm",
+      &[
+        ("pnpm-workspace.yaml", "packages:\n  - 'libs/*'\n"),
+        ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+        ("libs/a/package.json", "{}"),
+        ("packages/b/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["libs/a"]);
+  }
+
+  #[test]
+  fn reads_lerna_packages() {
+    let root = tree(
+      "workspace-lerna-default",
+      &[
+        ("lerna.json", r#"{ "version": "independent" }"#),
+        ("packages/a/package.json", "{}"),
+        ("modules/b/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["packages/a"]);
+
+    let root = tree(
+      "workspace-lerna",
+      &[
+        ("lerna.json", r#"{ "packages": ["modules/*"] }"#),
+        ("package.json", r#"{ "name": "root" }"#),
+        ("modules/b/package.json", "{}"),
+      ],
+    );
+    assert_eq!(dirs(&root), ["modules/b"]);
+  }
+
+  #[test]
+  fn finds_no_workspace() {
+    let root = tree(
+      "workspace-none",
+      &[("package.json", r#"{ "name": "app" }"#)],
+    );
+    assert!(Workspace::discover(&root).unwrap().is_none());
+    assert!(Workspace::enclosing(&root).unwrap().is_none());
+  }
+
+  #[test]
+  fn rejects_invalid_patterns() {
+    let root = tree("workspace-invalid", &[]);
+    let error = expand(&root, &["!packages/[".to_string()]).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidArgs);
+  }
+
+  #[test]
+  fn references_workspace_dependencies() {
+    let root = tree(
+      "workspace-references",
+      &[
+        (
+          "package.json",
+          r#"{ "workspaces": ["packages/*", "apps/*"] }"#,
+        ),
+        (
+          "apps/web/package.json",
+          r#"{ "name": "web", "private": true, "dependencies": { "ui": "*", "react": "^18" }, "devDependencies": { "utils": "*", "web": "*" } }"#,
+        ),
+        (
+          "packages/ui/package.json",
+          r#"{ "name": "ui", "exports": "./dist/index.js", "peerDependencies": { "utils": "*" } }"#,
+        ),
+        (
+          "packages/utils/package.json",
+          r#"{ "name": "utils", "private": true, "main": "index.js" }"#,
+        ),
+      ],
+    );
+    let workspace = Workspace::enclosing(&root.join("packages/ui/src"))
+      .unwrap()
+      .unwrap();
+    let web = workspace.package_at(&root.join("apps/web")).unwrap();
+    let ui = workspace.package_at(Path::new("packages/ui")).unwrap();
+    let utils = workspace.package_named("utils").unwrap();
+    assert_eq!(
+      workspace.references(web),
+      ["../../packages/ui", "../../packages/utils"]
+    );
+    assert_eq!(workspace.references(ui), ["../utils"]);
+    assert!(workspace.references(utils).is_empty());
+    assert!(!web.is_library());
+    assert!(ui.is_library());
+    assert!(!utils.is_library());
+  }
+
+  #[test]
+  fn relative_paths() {
+    assert_eq!(relative("packages/a", "packages/b"), "../b");
+    assert_eq!(relative("apps/web", "packages/ui"), "../../packages/ui");
+    assert_eq!(relative("a/b/c", "a"), "../..");
+    assert_eq!(relative("", "packages/a"), "packages/a");
+    assert_eq!(relative("packages/a/", "packages/a"), ".");
+    assert_eq!(relative("/tmp/x", "/tmp/x/y"), "y");
+  }
+}
@@ -0,0 +1,240 @@
+//! Writes generated config files, honouring `--force`, `--merge`,
+//! `--dry-run` and `--stdout`.
+
+use crate::diff;
+use crate::error::{Error, ErrorCode, Result};
+use crate::jsonc;
+use crate::merge::{self, Conflict, ConflictResolution};
+use crate::CliArgs;
+use dialoguer::Select;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What to do when a config file we are about to write already exists.
+#[derive(Debug, Clone, Copy)]
+pub enum ExistingAction {
+  Overwrite,
+  Merge,
+  Abort,
+}
+
+/// What `write_config` did (or, with `--dry-run`, would do) with a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Written {
+  Created,
+  Replaced,
+  Merged,
+  Skipped,
+}
+
+/// Writes `config` as JSON to `path`. An existing file is replaced, merged
+/// into or left alone depending on the flags (or the user's answer), and a
+/// `.bak` copy is kept before it changes. Also returns the config the file
+/// ends up holding, which after a merge or with the file left alone is not
+/// `config`.
+pub fn write_config(args: &CliArgs, path: &Path, config: &Value) -> Result<(Written, Value)> {
+  let existing = read_existing(path)?;
+  let (contents, written) = match &existing {
+    None => (serde_json::to_string_pretty(config)?, Written::Created),
+    Some(existing) => match existing_action(args, path)? {
+      ExistingAction::Overwrite => (serde_json::to_string_pretty(config)?, Written::Replaced),
+      ExistingAction::Merge => (merge_into(args, existing, config)?, Written::Merged),
+      ExistingAction::Abort => {
+        println!("Left {} untouched", path.display());
+        let kept = jsonc::Document::parse(existing)?.value();
+        return Ok((Written::Skipped, kept));
+      }
+    },
+  };
+
+  write_contents(args, path, existing.as_deref(), &contents)?;
+  let written_config = match written {
+    Written::Merged => jsonc::Document::parse(&contents)?.value(),
+    _ => config.clone(),
+  };
+  Ok((written, written_config))
+}
+
+/// The contents of the file at `path`, or `None` if there is none yet.
+pub fn read_existing(path: &Path) -> Result<Option<String>> {
+  match fs::read_to_string(path) {
+    Ok(existing) => Ok(Some(existing)),
+    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(e.into()),
+  }
+}
+
+/// Writes `contents` to `path`, or only shows them with `--stdout` and
+/// `--dry-run`. The `existing` contents are backed up first.
+pub fn write_contents(
+  args: &CliArgs,
+  path: &Path,
+  existing: Option<&str>,
+  contents: &str,
+) -> Result<()> {
+  if args.stdout {
+    println!("{}", contents);
+    return Ok(());
+  }
+  if args.dry_run {
+    println!("Would write {}", path.display());
+    match existing {
+      Some(existing) => diff::print_diff(path, existing, contents),
+      None => println!("{}", contents),
+    }
+    return Ok(());
+  }
+
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  if existing.is_some() {
+    let backup_path = backup_path(path);
+    fs::copy(path, &backup_path)?;
+    println!("Backed up the previous config to {}", backup_path.display());
+  }
+  fs::write(path, contents)?;
+  Ok(())
+}
+
+/// The first of `<file>.bak`, `<file>.bak.1`, `<file>.bak.2`, ... that does
+/// not exist yet, so earlier backups are never overwritten.
+fn backup_path(path: &Path) -> PathBuf {
+  std::iter::once(path.with_extension("json.bak"))
+    .chain((1..).map(|n| path.with_extension(format!("json.bak.{}", n))))
+    .find(|backup| !backup.exists())
+    .unwrap()
+}
+
+/// Merges `tsconfig` into the `existing` file contents, editing them in place
+/// so comments and layout are kept.
+fn merge_into(args: &CliArgs, existing: &str, tsconfig: &Value) -> Result<String> {
+  let mut document = jsonc::Document::parse(existing)?;
+  let existing = document.value();
+  let mut resolutions = HashMap::new();
+  for conflict in merge::conflicts(&existing, tsconfig) {
+    let resolution = resolve_conflict(args, &conflict)?;
+    resolutions.insert(conflict.key, resolution);
+  }
+
+  let merged = merge::merge_tsconfig(
+    existing,
+    tsconfig,
+    &resolutions,
+    ConflictResolution::Existing,
+  );
+  document.update(&merged)?;
+  Ok(document.to_string())
+}
+
+/// Whether there is someone to ask: not told to take the defaults with
+/// `--yes`, and with a terminal on stderr, where dialoguer prompts.
+fn can_prompt(args: &CliArgs) -> bool {
+  !args.yes && console::user_attended_stderr()
+}
+
+/// Asks how to handle an existing tsconfig. Without a terminal to ask on
+/// (or with `--yes`), replacing it requires `--force`.
+fn existing_action(args: &CliArgs, path: &Path) -> Result<ExistingAction> {
+  if args.force {
+    return Ok(ExistingAction::Overwrite);
+  }
+  if args.merge {
+    return Ok(ExistingAction::Merge);
+  }
+  // Nothing gets written, so show what replacing the file would look like
+  if args.stdout || (args.dry_run && !can_prompt(args)) {
+    return Ok(ExistingAction::Overwrite);
+  }
+  if !can_prompt(args) {
+    return Err(Error::new(
+      ErrorCode::Exists,
+      format!(
+        "{} already exists, pass --force to replace it or --merge to merge into it",
+        path.display()
+      ),
+    ));
+  }
+
+  let actions = &[
+    "Overwrite it",
+    "Merge the generated options into it",
+    "Abort",
+  ];
+  let action_idx = Select::new()
+    .with_prompt(format!(
+      "{} already exists, what should we do?",
+      path.display()
+    ))
+    .default(1)
+    .items(actions)
+    .interact()?;
+
+  Ok(match action_idx {
+    0 => ExistingAction::Overwrite,
+    1 => ExistingAction::Merge,
+    2 => ExistingAction::Abort,
+    _ => unreachable!(),
+  })
+}
+
+/// Picks which value of a conflicting compiler option to keep, asking unless
+/// `--on-conflict` or `--yes` already decided (`--yes`, like having no
+/// terminal to ask on, keeps the existing one).
+fn resolve_conflict(args: &CliArgs, conflict: &Conflict) -> Result<ConflictResolution> {
+  let resolution = match args.on_conflict {
+    Some(resolution) => resolution,
+    None if !can_prompt(args) => ConflictResolution::Existing,
+    None => {
+      let choices = &[
+        format!("Keep {}", conflict.existing),
+        format!("Use {}", conflict.generated),
+      ];
+      let choice_idx = Select::new()
+        .with_prompt(format!(
+          "compilerOptions.{} is already set, which value should we use?",
+          conflict.key
+        ))
+        .default(0)
+        .items(choices)
+        .interact()?;
+
+      match choice_idx {
+        0 => ConflictResolution::Existing,
+        1 => ConflictResolution::Generated,
+        _ => unreachable!(),
+      }
+    }
+  };
+
+  let (kept, dropped) = match resolution {
+    ConflictResolution::Existing => (&conflict.existing, &conflict.generated),
+    ConflictResolution::Generated => (&conflict.generated, &conflict.existing),
+  };
+  eprintln!(
+    "compilerOptions.{}: using {} instead of {}",
+    conflict.key, kept, dropped
+  );
+  Ok(resolution)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::tree;
+
+  #[test]
+  fn backups_are_numbered_instead_of_overwritten() {
+    let dir = tree("write-backup", &[]);
+    let path = dir.join("tsconfig.json");
+
+    assert_eq!(backup_path(&path), dir.join("tsconfig.json.bak"));
+    fs::write(dir.join("tsconfig.json.bak"), "").unwrap();
+    assert_eq!(backup_path(&path), dir.join("tsconfig.json.bak.1"));
+    fs::write(dir.join("tsconfig.json.bak.1"), "").unwrap();
+    assert_eq!(backup_path(&path), dir.join("tsconfig.json.bak.2"));
+  }
+}